@@ -1,6 +1,8 @@
 use std::path::PathBuf;
 use structopt::StructOpt;
 
+use crate::command::board::Command as BoardCommand;
+use crate::command::issues::Command as IssuesCommand;
 use crate::command::sprint::Command as SprintCommand;
 
 /// Github and Zenhub toolkit. Octocat++.
@@ -13,9 +15,13 @@ use crate::command::sprint::Command as SprintCommand;
 )]
 pub struct Args {
     #[structopt(long = "config", parse(from_os_str))]
-    /// Defaults to ./decadog.yml
+    /// Defaults to the first of ./decadog.{yml,yaml,toml,json} found
     pub config: Option<PathBuf>,
 
+    #[structopt(long = "no-color")]
+    /// Disable colored output. Also disabled by setting NO_COLOR
+    pub no_color: bool,
+
     /// Subcommand selected.
     #[structopt(subcommand)]
     pub command: Command,
@@ -23,6 +29,24 @@ pub struct Args {
 
 #[derive(Debug, StructOpt)]
 pub enum Command {
+    #[structopt(name = "board")]
+    /// Inspect the Zenhub board.
+    Board {
+        #[structopt(subcommand)]
+        command: BoardCommand,
+    },
+
+    #[structopt(name = "doctor")]
+    /// Check configuration, credentials and connectivity.
+    Doctor,
+
+    #[structopt(name = "issues")]
+    /// Inspect issues.
+    Issues {
+        #[structopt(subcommand)]
+        command: IssuesCommand,
+    },
+
     #[structopt(name = "sprint")]
     /// Manage sprints.
     Sprint {
@@ -0,0 +1,147 @@
+use decadog_core::github;
+use decadog_core::zenhub::{self, Board, Workspace};
+use serde_derive::Serialize;
+use structopt::StructOpt;
+
+use crate::{error::Error, Settings};
+
+/// A Zenhub board, with the workspace it belongs to.
+#[derive(Debug, Serialize)]
+struct BoardDump {
+    workspace: Workspace,
+    board: Board,
+}
+
+/// Find a workspace by name or id.
+fn find_workspace(workspaces: Vec<Workspace>, name_or_id: &str) -> Option<Workspace> {
+    workspaces.into_iter().find(|workspace| {
+        workspace.id == name_or_id || workspace.name.as_deref() == Some(name_or_id)
+    })
+}
+
+/// Print the Zenhub board as JSON, from the given workspace or the first.
+fn dump_board(settings: &Settings, workspace: Option<&str>) -> Result<(), Error> {
+    let github = github::Client::new(&settings.github_url, &settings.github_token.value())?;
+    let zenhub = zenhub::Client::new(
+        settings.zenhub_url.as_ref().ok_or(Error::Settings {
+            description: "Zenhub url required to dump board.".to_owned(),
+        })?,
+        &settings
+            .zenhub_token
+            .as_ref()
+            .ok_or(Error::Settings {
+                description: "Zenhub token required to dump board.".to_owned(),
+            })?
+            .value(),
+    )?;
+    let client = settings.client(&github, &zenhub)?;
+
+    let repository = client.get_repository()?;
+    let workspace = match workspace {
+        Some(name_or_id) => find_workspace(client.get_workspaces(&repository)?, name_or_id)
+            .ok_or_else(|| Error::User {
+                description: format!("No Zenhub workspace '{}' found for repository.", name_or_id),
+            })?,
+        None => client.get_first_workspace(&repository)?,
+    };
+    let board = client.get_board(&repository, &workspace)?;
+
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&BoardDump { workspace, board })
+            .expect("Board dump is always serializable.")
+    );
+    Ok(())
+}
+
+#[derive(Debug, StructOpt)]
+pub enum Command {
+    #[structopt(name = "dump")]
+    /// Print the Zenhub board as JSON.
+    Dump {
+        /// Workspace name or id. Defaults to the first workspace of the repository.
+        #[structopt(long = "workspace")]
+        workspace: Option<String>,
+    },
+}
+
+pub fn run(command: &Command, settings: &Settings) -> Result<(), Error> {
+    match command {
+        Command::Dump { workspace } => dump_board(settings, workspace.as_deref()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use decadog_core::zenhub::{Estimate, Pipeline, PipelineIssue};
+    use serde_json::json;
+
+    use super::*;
+
+    fn workspace(id: &str, name: &str) -> Workspace {
+        Workspace {
+            name: Some(name.to_owned()),
+            description: None,
+            id: id.to_owned(),
+            repositories: vec![1234],
+        }
+    }
+
+    #[test]
+    fn find_workspace_by_name_or_id() {
+        let workspaces = vec![workspace("ws1", "Team"), workspace("ws2", "Roadmap")];
+        assert_eq!(find_workspace(workspaces.clone(), "ws2").unwrap().id, "ws2");
+        assert_eq!(
+            find_workspace(workspaces.clone(), "Roadmap").unwrap().id,
+            "ws2"
+        );
+        assert_eq!(find_workspace(workspaces, "Other"), None);
+    }
+
+    #[test]
+    fn board_dump_serialize() {
+        let dump = BoardDump {
+            workspace: workspace("ws1", "Team"),
+            board: Board {
+                pipelines: vec![
+                    Pipeline {
+                        id: "p1".to_owned(),
+                        name: "Backlog".to_owned(),
+                        issues: vec![PipelineIssue {
+                            issue_number: 7,
+                            estimate: Some(Estimate { value: 3 }),
+                            is_epic: false,
+                        }],
+                    },
+                    Pipeline {
+                        id: "p2".to_owned(),
+                        name: "Done".to_owned(),
+                        issues: vec![PipelineIssue {
+                            issue_number: 9,
+                            estimate: None,
+                            is_epic: true,
+                        }],
+                    },
+                ],
+            },
+        };
+
+        let value = serde_json::to_value(&dump).unwrap();
+        assert_eq!(value["workspace"]["id"], json!("ws1"));
+        assert_eq!(
+            value["board"]["pipelines"],
+            json!([
+                {
+                    "id": "p1",
+                    "name": "Backlog",
+                    "issues": [{"issue_number": 7, "estimate": {"value": 3}, "is_epic": false}]
+                },
+                {
+                    "id": "p2",
+                    "name": "Done",
+                    "issues": [{"issue_number": 9, "estimate": null, "is_epic": true}]
+                }
+            ])
+        );
+    }
+}
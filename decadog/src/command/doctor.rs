@@ -0,0 +1,239 @@
+use std::fmt;
+
+use colored::Colorize;
+use decadog_core::github::{self, Repository};
+use decadog_core::zenhub;
+
+use crate::{error::Error, Settings};
+
+/// Outcome of a single check, with a human readable detail or error.
+#[derive(Debug, Clone, PartialEq)]
+struct Check {
+    name: &'static str,
+    result: Result<String, String>,
+}
+
+impl fmt::Display for Check {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.result {
+            Ok(detail) => write!(f, "{} {}: {}", "PASS".green(), self.name, detail),
+            Err(error) => write!(f, "{} {}: {}", "FAIL".red(), self.name, error),
+        }
+    }
+}
+
+/// Outcomes of all checks.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Report {
+    checks: Vec<Check>,
+}
+
+impl Report {
+    fn push(&mut self, name: &'static str, result: Result<String, String>) {
+        self.checks.push(Check { name, result });
+    }
+
+    fn failures(&self) -> usize {
+        self.checks
+            .iter()
+            .filter(|check| check.result.is_err())
+            .count()
+    }
+
+    fn passed(&self) -> bool {
+        self.failures() == 0
+    }
+}
+
+fn check_github_repository(
+    github: &github::Client,
+    settings: &Settings,
+) -> Result<Repository, String> {
+    github
+        .get_repository(&settings.owner, &settings.repo)
+        .map_err(|error| error.to_string())
+}
+
+fn check_github_rate_limit(github: &github::Client) -> Result<String, String> {
+    let rate_limit = github
+        .get_rate_limit()
+        .map_err(|error| error.to_string())?
+        .rate;
+    if rate_limit.remaining == 0 {
+        Err(format!(
+            "exhausted, 0/{} requests remaining",
+            rate_limit.limit
+        ))
+    } else {
+        Ok(format!(
+            "{}/{} requests remaining",
+            rate_limit.remaining, rate_limit.limit
+        ))
+    }
+}
+
+fn check_zenhub(settings: &Settings, repository: Option<&Repository>) -> Result<String, String> {
+    let url = settings
+        .zenhub_url
+        .as_ref()
+        .ok_or("Zenhub url not configured")?;
+    let token = settings
+        .zenhub_token
+        .as_ref()
+        .ok_or("Zenhub token not configured")?;
+    let repository = repository.ok_or("skipped, as the Github repository is unavailable")?;
+
+    let zenhub = zenhub::Client::new(url, token.value()).map_err(|error| error.to_string())?;
+    let workspace = zenhub
+        .get_first_workspace(repository.id)
+        .map_err(|error| error.to_string())?;
+    Ok(format!(
+        "token valid, found workspace {}",
+        workspace.name.as_deref().unwrap_or(&workspace.id)
+    ))
+}
+
+/// Run all checks. No checks mutate any state.
+fn run_checks(settings: &Settings) -> Report {
+    let mut report = Report::default();
+    report.push(
+        "Repository",
+        Ok(format!("{}/{}", settings.owner, settings.repo)),
+    );
+
+    let github = match github::Client::new(&settings.github_url, settings.github_token.value()) {
+        Ok(github) => github,
+        Err(error) => {
+            report.push("Github", Err(error.to_string()));
+            report.push("Zenhub", check_zenhub(settings, None));
+            return report;
+        }
+    };
+
+    let repository = check_github_repository(&github, settings);
+    report.push(
+        "Github",
+        repository
+            .as_ref()
+            .map(|repository| format!("token valid, found {}", repository.full_name))
+            .map_err(Clone::clone),
+    );
+    report.push("Github rate limit", check_github_rate_limit(&github));
+    report.push("Zenhub", check_zenhub(settings, repository.ok().as_ref()));
+    report
+}
+
+/// Check configuration, credentials and connectivity.
+pub fn run(settings: &Settings) -> Result<(), Error> {
+    let report = run_checks(settings);
+    for check in report.checks.iter() {
+        println!("{}", check);
+    }
+
+    if report.passed() {
+        Ok(())
+    } else {
+        Err(Error::User {
+            description: format!(
+                "{} of {} checks failed.",
+                report.failures(),
+                report.checks.len()
+            ),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mockito::mock;
+
+    use super::*;
+
+    fn mock_settings(repo: &str, zenhub: bool) -> Settings {
+        let mut settings = config::Config::default();
+        settings.set("owner", "tommilligan").unwrap();
+        settings.set("repo", repo).unwrap();
+        settings.set("github_url", mockito::server_url()).unwrap();
+        settings.set("github_token", "mock_token").unwrap();
+        if zenhub {
+            settings.set("zenhub_url", mockito::server_url()).unwrap();
+            settings.set("zenhub_token", "mock_token").unwrap();
+        }
+        settings.try_into().unwrap()
+    }
+
+    #[test]
+    fn report_aggregation() {
+        let mut report = Report::default();
+        report.push("First", Ok("fine".to_owned()));
+        assert!(report.passed());
+
+        report.push("Second", Err("broken".to_owned()));
+        report.push("Third", Ok("fine".to_owned()));
+        assert!(!report.passed());
+        assert_eq!(report.failures(), 1);
+    }
+
+    #[test]
+    fn all_checks_pass() {
+        let _repository = mock("GET", "/repos/tommilligan/decadog-doctor")
+            .match_header("authorization", "token mock_token")
+            .with_status(200)
+            .with_body(r#"{"id": 4321, "name": "decadog-doctor", "full_name": "tommilligan/decadog-doctor"}"#)
+            .create();
+        let _rate_limit = mock("GET", "/rate_limit")
+            .match_header("authorization", "token mock_token")
+            .with_status(200)
+            .with_body(r#"{"rate": {"limit": 5000, "remaining": 4999, "reset": 1372700873}}"#)
+            .create();
+        let _workspaces = mock("GET", "/p2/repositories/4321/workspaces")
+            .match_header("x-authentication-token", "mock_token")
+            .with_status(200)
+            .with_body(
+                r#"[{"name": "Team", "description": null, "id": "ws1", "repositories": [4321]}]"#,
+            )
+            .create();
+
+        let report = run_checks(&mock_settings("decadog-doctor", true));
+
+        assert_eq!(
+            report.checks,
+            vec![
+                Check {
+                    name: "Repository",
+                    result: Ok("tommilligan/decadog-doctor".to_owned()),
+                },
+                Check {
+                    name: "Github",
+                    result: Ok("token valid, found tommilligan/decadog-doctor".to_owned()),
+                },
+                Check {
+                    name: "Github rate limit",
+                    result: Ok("4999/5000 requests remaining".to_owned()),
+                },
+                Check {
+                    name: "Zenhub",
+                    result: Ok("token valid, found workspace Team".to_owned()),
+                },
+            ]
+        );
+        assert!(report.passed());
+    }
+
+    #[test]
+    fn failed_checks_reported() {
+        let _repository = mock("GET", "/repos/tommilligan/decadog-missing")
+            .with_status(404)
+            .with_body(r#"{"message": "Not Found", "documentation_url": "https://developer.github.com/v3"}"#)
+            .create();
+
+        let report = run_checks(&mock_settings("decadog-missing", false));
+
+        assert!(!report.passed());
+        assert!(report.checks[1].result.is_err());
+        assert_eq!(
+            report.checks[3].result,
+            Err("Zenhub url not configured".to_owned())
+        );
+    }
+}
@@ -0,0 +1,456 @@
+use std::fmt;
+
+use colored::Colorize;
+use decadog_core::github::{
+    self, Direction, Issue, IssueUpdate, SearchIssues, SearchQueryBuilder, SearchState, State,
+};
+use decadog_core::{zenhub, Error as DecadogError};
+use serde_derive::Serialize;
+use structopt::StructOpt;
+
+use crate::command::LISTING_TITLE_WIDTH;
+use crate::{error::Error, Settings};
+
+/// Parse an issue state filter.
+fn parse_search_state(state: &str) -> Result<SearchState, String> {
+    match state {
+        "open" => Ok(SearchState::Open),
+        "closed" => Ok(SearchState::Closed),
+        "all" => Ok(SearchState::All),
+        _ => Err(format!(
+            "Invalid state '{}'; use open, closed or all",
+            state
+        )),
+    }
+}
+
+/// Output format for a single issue.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum IssueFormat {
+    Text,
+    Json,
+}
+
+/// Parse an issue output format.
+fn parse_issue_format(format: &str) -> Result<IssueFormat, String> {
+    match format {
+        "text" => Ok(IssueFormat::Text),
+        "json" => Ok(IssueFormat::Json),
+        _ => Err(format!("Invalid format '{}'; use text or json", format)),
+    }
+}
+
+/// A Github issue, with its Zenhub metadata.
+#[derive(Debug, Serialize)]
+struct IssueDetail {
+    issue: Issue,
+    zenhub: zenhub::Issue,
+}
+
+impl fmt::Display for IssueDetail {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let issue = &self.issue;
+        let or_none = |values: Vec<&str>| {
+            if values.is_empty() {
+                "none".to_owned()
+            } else {
+                values.join(", ")
+            }
+        };
+        writeln!(f, "{}", issue.to_string().bold())?;
+        writeln!(
+            f,
+            "State:     {}",
+            match issue.state {
+                State::Open => "open".green(),
+                State::Closed => "closed".red(),
+            }
+        )?;
+        writeln!(
+            f,
+            "Milestone: {}",
+            or_none(issue.milestone.iter().map(|m| m.title.as_str()).collect())
+        )?;
+        writeln!(
+            f,
+            "Assignees: {}",
+            or_none(issue.assignees.iter().map(|a| a.login.as_str()).collect())
+        )?;
+        writeln!(
+            f,
+            "Labels:    {}",
+            or_none(issue.labels.iter().map(|l| l.name.as_str()).collect())
+        )?;
+        match &self.zenhub.estimate {
+            Some(estimate) => writeln!(f, "Estimate:  {}", estimate.value)?,
+            None => writeln!(f, "Estimate:  none")?,
+        }
+        writeln!(
+            f,
+            "Epic:      {}",
+            if self.zenhub.is_epic { "yes" } else { "no" }
+        )?;
+        write!(f, "{}", issue.html_url)
+    }
+}
+
+/// Query for issues in a milestone, in the given state.
+fn milestone_issues_query(milestone_title: &str, state: &SearchState) -> SearchQueryBuilder {
+    let mut query = SearchQueryBuilder::new();
+    query.milestone(milestone_title);
+    match state {
+        SearchState::Open => {
+            query.state(&State::Open);
+        }
+        SearchState::Closed => {
+            query.state(&State::Closed);
+        }
+        // All states are returned if no state is given
+        SearchState::All => (),
+    }
+    query
+}
+
+fn list_issues(settings: &Settings, milestone: &str, state: &SearchState) -> Result<(), Error> {
+    let github = github::Client::new(&settings.github_url, &settings.github_token.value())?;
+
+    let q = milestone_issues_query(milestone, state)
+        .owner_repo(&settings.owner, &settings.repo)
+        .issue()
+        .build()
+        .to_owned();
+    let query = SearchIssues {
+        q: &q,
+        sort: Some("created"),
+        order: Some(Direction::Ascending),
+        per_page: Some(100),
+    };
+    let issues = github::exclude_pull_requests(github.search_issues(&query)?)
+        .collect::<Result<Vec<Issue>, _>>()?;
+
+    if issues.is_empty() {
+        eprintln!("No issues found.");
+    }
+    for issue in issues.iter() {
+        let state = match issue.state {
+            State::Open => "open".green(),
+            State::Closed => "closed".red(),
+        };
+        println!(
+            "{:>6}  {:<6}  {}",
+            issue.number,
+            state,
+            issue.truncated_title(LISTING_TITLE_WIDTH)
+        );
+    }
+    Ok(())
+}
+
+/// Show an issue from Github, with its Zenhub estimate and epic status.
+fn show_issue(settings: &Settings, issue_number: u32, format: IssueFormat) -> Result<(), Error> {
+    let github = github::Client::new(&settings.github_url, &settings.github_token.value())?;
+    let zenhub = zenhub::Client::new(
+        settings.zenhub_url.as_ref().ok_or(Error::Settings {
+            description: "Zenhub url required to show issue.".to_owned(),
+        })?,
+        &settings
+            .zenhub_token
+            .as_ref()
+            .ok_or(Error::Settings {
+                description: "Zenhub token required to show issue.".to_owned(),
+            })?
+            .value(),
+    )?;
+    let client = settings.client(&github, &zenhub)?;
+
+    let repository = client.get_repository()?;
+    let issue = client
+        .get_issue(issue_number)
+        .map_err(|error| match error {
+            DecadogError::Github { status, .. } | DecadogError::Api { status, .. }
+                if status.as_u16() == 404 =>
+            {
+                Error::User {
+                    description: format!(
+                        "Issue #{} not found in {}/{}.",
+                        issue_number, settings.owner, settings.repo
+                    ),
+                }
+            }
+            error => error.into(),
+        })?;
+    let zenhub = client.get_zenhub_issue(&repository, &issue)?;
+    let detail = IssueDetail { issue, zenhub };
+
+    match format {
+        IssueFormat::Text => println!("{}", detail),
+        IssueFormat::Json => println!(
+            "{}",
+            serde_json::to_string_pretty(&detail).expect("Issue detail is always serializable.")
+        ),
+    }
+    Ok(())
+}
+
+/// Assign an issue to an organisation member, and optionally a milestone.
+fn assign_issue(
+    settings: &Settings,
+    issue_number: u32,
+    login: &str,
+    milestone_title: Option<&str>,
+) -> Result<(), Error> {
+    // Only Github is needed, so this works without Zenhub configured
+    let github = github::Client::new(&settings.github_url, &settings.github_token.value())?;
+
+    let member = github.resolve_member(&settings.owner, login)?;
+    let mut update = IssueUpdate::default();
+    update.assignees = Some(vec![member.login.clone()]);
+    if let Some(milestone_title) = milestone_title {
+        let milestone = github
+            .get_milestone_by_title(&settings.owner, &settings.repo, milestone_title)?
+            .ok_or_else(|| Error::User {
+                description: format!("No milestone titled '{}'.", milestone_title),
+            })?;
+        update.milestone = Some(Some(milestone.number));
+    }
+    let issue = github.patch_issue(&settings.owner, &settings.repo, issue_number, &update)?;
+
+    println!("Assigned #{} to {}.", issue.number, member.login);
+    Ok(())
+}
+
+#[derive(Debug, StructOpt)]
+pub enum Command {
+    #[structopt(name = "assign")]
+    /// Assign an issue to an organisation member.
+    Assign {
+        /// Issue number.
+        issue_number: u32,
+
+        /// Github login of the member to assign.
+        #[structopt(long = "to")]
+        login: String,
+
+        /// Also assign the issue to the milestone with this title.
+        #[structopt(long = "milestone")]
+        milestone: Option<String>,
+    },
+
+    #[structopt(name = "show")]
+    /// Show an issue, with its Zenhub estimate and epic status.
+    Show {
+        /// Issue number.
+        issue_number: u32,
+
+        /// Output format: text or json.
+        #[structopt(long = "format", default_value = "text", parse(try_from_str = parse_issue_format))]
+        format: IssueFormat,
+    },
+
+    #[structopt(name = "list")]
+    /// List issues in a milestone.
+    List {
+        /// Milestone title.
+        #[structopt(long = "milestone")]
+        milestone: String,
+
+        /// Issue state: open, closed or all.
+        #[structopt(long = "state", default_value = "open", parse(try_from_str = parse_search_state))]
+        state: SearchState,
+    },
+}
+
+pub fn run(command: &Command, settings: &Settings) -> Result<(), Error> {
+    match command {
+        Command::Assign {
+            issue_number,
+            login,
+            milestone,
+        } => assign_issue(settings, *issue_number, login, milestone.as_deref()),
+        Command::List { milestone, state } => list_issues(settings, milestone, state),
+        Command::Show {
+            issue_number,
+            format,
+        } => show_issue(settings, *issue_number, *format),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mockito::mock;
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn issue_detail_serialize() {
+        let issue: Issue = serde_json::from_value(json!({
+            "id": 1234567,
+            "number": 12,
+            "state": "open",
+            "title": "Show issues",
+            "milestone": null,
+            "assignees": [{"login": "octocat", "id": 2}],
+            "labels": [{"id": 3, "name": "A-cli"}],
+            "created_at": "2020-05-01T12:00:00Z",
+            "updated_at": "2020-05-01T12:00:00Z",
+            "closed_at": null,
+            "html_url": "https://github.com/tommilligan/decadog/issues/12",
+            "pull_request": null
+        }))
+        .unwrap();
+        let detail = IssueDetail {
+            issue,
+            zenhub: zenhub::Issue {
+                estimate: Some(zenhub::Estimate { value: 3 }),
+                is_epic: false,
+            },
+        };
+
+        let value = serde_json::to_value(&detail).unwrap();
+        assert_eq!(value["issue"]["number"], json!(12));
+        assert_eq!(value["issue"]["assignees"][0]["login"], json!("octocat"));
+        assert_eq!(value["issue"]["labels"][0]["name"], json!("A-cli"));
+        assert_eq!(
+            value["zenhub"],
+            json!({"estimate": {"value": 3}, "is_epic": false})
+        );
+    }
+
+    #[test]
+    fn show_format_flag() {
+        match Command::from_iter_safe(&["issues", "show", "12", "--format", "json"]).unwrap() {
+            Command::Show {
+                issue_number,
+                format,
+            } => {
+                assert_eq!(issue_number, 12);
+                assert_eq!(format, IssueFormat::Json);
+            }
+            other => panic!("Expected show command, got {:?}", other),
+        }
+        assert!(Command::from_iter_safe(&["issues", "show", "12", "--format", "yaml"]).is_err());
+    }
+
+    #[test]
+    fn milestone_issues_query_state() {
+        assert_eq!(
+            milestone_issues_query("Sprint 2", &SearchState::Open).build(),
+            r#"milestone:"Sprint 2" state:open"#
+        );
+        assert_eq!(
+            milestone_issues_query("Sprint 2", &SearchState::Closed).build(),
+            r#"milestone:"Sprint 2" state:closed"#
+        );
+        assert_eq!(
+            milestone_issues_query("Sprint 2", &SearchState::All).build(),
+            r#"milestone:"Sprint 2""#
+        );
+    }
+
+    #[test]
+    fn state_flag() {
+        match Command::from_iter_safe(&["issues", "list", "--milestone", "Sprint 2"]).unwrap() {
+            Command::List { milestone, state } => {
+                assert_eq!(milestone, "Sprint 2");
+                assert_eq!(state, SearchState::Open);
+            }
+            other => panic!("Expected list command, got {:?}", other),
+        }
+        match Command::from_iter_safe(&["issues", "list", "--milestone", "v1", "--state", "all"])
+            .unwrap()
+        {
+            Command::List { state, .. } => assert_eq!(state, SearchState::All),
+            other => panic!("Expected list command, got {:?}", other),
+        }
+        assert!(Command::from_iter_safe(&[
+            "issues",
+            "list",
+            "--milestone",
+            "v1",
+            "--state",
+            "any"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn assign_without_zenhub() {
+        let mut settings = config::Config::default();
+        settings.set("owner", "tommilligan").unwrap();
+        settings.set("repo", "decadog-assign").unwrap();
+        settings.set("github_url", mockito::server_url()).unwrap();
+        settings.set("github_token", "mock_token").unwrap();
+        let settings: Settings = settings.try_into().unwrap();
+
+        let members = mock("GET", "/orgs/tommilligan/members?per_page=100")
+            .match_header("authorization", "token mock_token")
+            .with_body(r#"[{"login": "octocat", "id": 2}]"#)
+            .expect(2)
+            .create();
+        let milestones = mock(
+            "GET",
+            "/repos/tommilligan/decadog-assign/milestones?state=all&per_page=100",
+        )
+        .with_body(
+            r#"[{"id": 1, "number": 4, "state": "open", "title": "Sprint 2", "due_on": "2020-05-14T12:00:00Z"}]"#,
+        )
+        .create();
+        let patch = mock("PATCH", "/repos/tommilligan/decadog-assign/issues/12")
+            .match_body(r#"{"milestone":4,"assignees":["octocat"]}"#)
+            .with_body(
+                r#"{
+  "id": 1234567,
+  "number": 12,
+  "state": "open",
+  "title": "Assign me",
+  "assignees": [{"login": "octocat", "id": 2}],
+  "created_at": "2020-05-01T12:00:00Z",
+  "updated_at": "2020-05-01T12:00:00Z",
+  "html_url": "https://github.com/tommilligan/decadog-assign/issues/12"
+}"#,
+            )
+            .expect(1)
+            .create();
+
+        assign_issue(&settings, 12, "OctoCat", Some("Sprint 2")).unwrap();
+        match assign_issue(&settings, 12, "nobody", None) {
+            Err(Error::Decadog { source }) => assert_eq!(
+                source.to_string(),
+                "Not found: 'nobody' is not a member of tommilligan."
+            ),
+            other => panic!("Expected not found error, got {:?}", other),
+        }
+
+        members.assert();
+        milestones.assert();
+        patch.assert();
+    }
+
+    #[test]
+    fn assign_flags() {
+        match Command::from_iter_safe(&[
+            "issues",
+            "assign",
+            "12",
+            "--to",
+            "octocat",
+            "--milestone",
+            "Sprint 2",
+        ])
+        .unwrap()
+        {
+            Command::Assign {
+                issue_number,
+                login,
+                milestone,
+            } => {
+                assert_eq!(issue_number, 12);
+                assert_eq!(login, "octocat");
+                assert_eq!(milestone.as_deref(), Some("Sprint 2"));
+            }
+            other => panic!("Expected assign command, got {:?}", other),
+        }
+        assert!(Command::from_iter_safe(&["issues", "assign", "12"]).is_err());
+    }
+}
@@ -1 +1,7 @@
+pub mod board;
+pub mod doctor;
+pub mod issues;
 pub mod sprint;
+
+/// Maximum width of issue titles in listings.
+pub(crate) const LISTING_TITLE_WIDTH: usize = 72;
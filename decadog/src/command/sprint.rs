@@ -1,65 +1,300 @@
-use chrono::{DateTime, Duration, FixedOffset, Local};
+use std::collections::hash_map::{Entry, HashMap};
+use std::fmt;
+
+use chrono::{DateTime, Duration, FixedOffset, Local, NaiveDate};
 use colored::Colorize;
+use decadog_core::api::{GithubApi, ZenhubApi};
+use decadog_core::github::paginate::SEARCH_RESULT_CAP;
 use decadog_core::github::{
-    self, Milestone, OrganisationMember, Repository, SearchQueryBuilder, State,
+    self, Issue, Milestone, OrganisationMember, Repository, SearchQueryBuilder, State,
+};
+use decadog_core::zenhub::{self, Board, Estimate, MoveOutcome, Pipeline, Workspace};
+use decadog_core::{
+    compute_done_points_by_closed_at, AssignedTo, Client, Error as DecadogError, ReportIssue,
+    SprintPoints, SprintReport,
 };
-use decadog_core::zenhub::{self, Estimate, Pipeline, Workspace};
-use decadog_core::{AssignedTo, Client};
 use lazy_static::lazy_static;
 use log::error;
 use structopt::StructOpt;
 
+use crate::command::LISTING_TITLE_WIDTH;
 use crate::interact::{Confirm, FuzzySelect, Input, Select};
+use crate::parallel;
+use crate::plan::{Change, ChangePlan};
 use crate::{error::Error, Settings};
 
 lazy_static! {
     static ref ESTIMATES: Vec<Estimate> =
         [0u32, 1, 2, 3, 5, 8, 13].iter().map(Into::into).collect();
+    static ref ESTIMATE_CHOICES: Vec<EstimateChoice> = ESTIMATES
+        .iter()
+        .cloned()
+        .map(EstimateChoice::Preset)
+        .chain(Some(EstimateChoice::Custom))
+        .collect();
 }
 
-struct SprintPoints {
-    pub planned: u32,
-    pub in_milestone: u32,
-    pub in_milestone_open: u32,
-
-    pub done_in_sprint: u32,
-    pub done_out_of_sprint: u32,
-    pub done_total: u32,
-}
-
-impl SprintPoints {
-    pub fn new(planned: u32, in_milestone: u32, in_milestone_open: u32) -> Result<Self, Error> {
-        let done_in_sprint = planned
-            .checked_sub(in_milestone_open)
-            .ok_or_else(|| Error::User {
-                description:
-                    "Planned points too low: should be higher than points remaining in sprint."
-                        .to_owned(),
-            })?;
-        let done_out_of_sprint = in_milestone
-            .checked_sub(planned)
-            .ok_or_else(|| Error::User {
-                description:
-                    "Planned points too high: should be lower than all points in milestone."
-                        .to_owned(),
-            })?;
-        let done_total = done_in_sprint + done_out_of_sprint;
+/// An estimate from the preset scale, or a custom value to be entered.
+#[derive(Debug, Clone, PartialEq)]
+enum EstimateChoice {
+    Preset(Estimate),
+    Custom,
+}
 
-        Ok(Self {
-            planned,
-            in_milestone,
-            in_milestone_open,
+impl fmt::Display for EstimateChoice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EstimateChoice::Preset(estimate) => write!(f, "{}", estimate),
+            EstimateChoice::Custom => write!(f, "custom…"),
+        }
+    }
+}
+
+impl EstimateChoice {
+    /// Resolve the estimate value, using `custom` to get a custom value.
+    fn value<F>(&self, custom: F) -> Result<u32, Error>
+    where
+        F: FnOnce() -> Result<u32, Error>,
+    {
+        match self {
+            EstimateChoice::Preset(estimate) => Ok(estimate.value),
+            EstimateChoice::Custom => custom(),
+        }
+    }
+}
 
-            done_in_sprint,
-            done_out_of_sprint,
-            done_total,
+/// Parse a custom estimate, which must be a positive integer.
+fn parse_custom_estimate(input: &str) -> Result<u32, Error> {
+    match input.trim().parse::<u32>() {
+        Ok(estimate) if estimate > 0 => Ok(estimate),
+        _ => Err(Error::User {
+            description: format!(
+                "Invalid estimate '{}': must be a positive integer.",
+                input.trim()
+            ),
+        }),
+    }
+}
+
+/// Prompt for a custom estimate until a valid one is entered.
+fn prompt_custom_estimate() -> Result<u32, Error> {
+    loop {
+        let input = Input::<String>::new()
+            .with_prompt("Custom estimate")
+            .interact()?;
+        match parse_custom_estimate(&input) {
+            Ok(estimate) => return Ok(estimate),
+            Err(error) => eprintln!("{}", error),
+        }
+    }
+}
+
+/// Index of the estimate choice to highlight by default, for an issue's current estimate.
+///
+/// This is the current estimate if it is on the preset scale, or the custom choice if it is
+/// not. Without a current estimate, the first choice is highlighted.
+fn estimate_default_index(current: Option<&Estimate>) -> usize {
+    let current = match current {
+        Some(current) => current,
+        None => return 0,
+    };
+    ESTIMATE_CHOICES
+        .iter()
+        .position(|choice| match choice {
+            EstimateChoice::Preset(estimate) => estimate == current,
+            EstimateChoice::Custom => true,
         })
+        .expect("Estimate choices always include custom.")
+}
+
+/// Prompt for an estimate from the preset scale, or a custom value.
+///
+/// The issue's `current` estimate is highlighted by default, so it is easy to keep.
+fn prompt_estimate(
+    select: &Select<EstimateChoice>,
+    current: Option<&Estimate>,
+) -> Result<u32, Error> {
+    select
+        .interact_with_default(estimate_default_index(current))?
+        .value(prompt_custom_estimate)
+}
+
+/// Response to the planned points prompt.
+#[derive(Debug, PartialEq)]
+enum PointsInput {
+    Points(u32),
+    Quit,
+    Empty,
+}
+
+/// Parse a response to the planned points prompt, ignoring surrounding whitespace.
+fn parse_points_input(input: &str) -> Result<PointsInput, Error> {
+    match input.trim() {
+        "" => Ok(PointsInput::Empty),
+        "q" | "quit" => Ok(PointsInput::Quit),
+        input => input
+            .parse()
+            .map(PointsInput::Points)
+            .map_err(|_| Error::User {
+                description: format!("Invalid number of planned points '{}'.", input),
+            }),
+    }
+}
+
+/// Prompt for planned points until a valid number is entered, or `None` to quit.
+fn prompt_planned_points() -> Result<Option<u32>, Error> {
+    loop {
+        let input = Input::<String>::new()
+            .with_prompt("Points planned this sprint (q: quit)")
+            .allow_empty(true)
+            .interact()?;
+        match parse_points_input(&input) {
+            Ok(PointsInput::Points(points)) => return Ok(Some(points)),
+            Ok(PointsInput::Quit) => return Ok(None),
+            Ok(PointsInput::Empty) => continue,
+            Err(error) => eprintln!("{}", error),
+        }
+    }
+}
+
+/// Prompt for planned points until they are consistent with the points in the milestone.
+///
+/// Returns `None` if the user quits.
+fn prompt_sprint_points(
+    in_milestone: u32,
+    in_milestone_open: u32,
+) -> Result<Option<SprintPoints>, Error> {
+    loop {
+        let planned = match prompt_planned_points()? {
+            Some(planned) => planned,
+            None => return Ok(None),
+        };
+        match SprintPoints::new(planned, in_milestone, in_milestone_open) {
+            Ok(points) => return Ok(Some(points)),
+            Err(DecadogError::Points { description }) => eprintln!("{}", description.yellow()),
+            Err(error) => return Err(error.into()),
+        }
+    }
+}
+
+/// Open points assigned to each organisation member.
+#[derive(Debug, Default, PartialEq)]
+struct Capacity {
+    /// Points by member login. Issues with several assignees count in full for each.
+    assigned: HashMap<String, u32>,
+    unassigned: u32,
+}
+
+impl Capacity {
+    /// Attribute the points of each issue to its assignees.
+    fn from_points<'i, I>(issue_points: I) -> Self
+    where
+        I: IntoIterator<Item = (&'i Issue, u32)>,
+    {
+        let mut capacity = Self::default();
+        for (issue, points) in issue_points {
+            if issue.assignees.is_empty() {
+                capacity.unassigned += points;
+            }
+            for assignee in &issue.assignees {
+                *capacity.assigned.entry(assignee.login.clone()).or_default() += points;
+            }
+        }
+        capacity
+    }
+
+    /// Members and their points, most loaded first.
+    fn sorted(&self) -> Vec<(&str, u32)> {
+        let mut sorted = self
+            .assigned
+            .iter()
+            .map(|(login, points)| (login.as_str(), *points))
+            .collect::<Vec<_>>();
+        sorted.sort_by(|(a_login, a_points), (b_login, b_points)| {
+            b_points.cmp(a_points).then_with(|| a_login.cmp(b_login))
+        });
+        sorted
+    }
+}
+
+fn no_zenhub_board_error() -> Error {
+    Error::User {
+        description: "No Zenhub board found; configure Zenhub for this repository first."
+            .to_owned(),
+    }
+}
+
+/// Order pipelines by name as listed in `order`, followed by any unlisted in board order.
+fn order_pipelines(pipelines: Vec<Pipeline>, order: &[String]) -> Vec<Pipeline> {
+    let (mut listed, unlisted): (Vec<_>, Vec<_>) = pipelines
+        .into_iter()
+        .partition(|pipeline| order.contains(&pipeline.name));
+    listed.sort_by_key(|pipeline| order.iter().position(|name| name == &pipeline.name));
+    listed.extend(unlisted);
+    listed
+}
+
+/// Build pipeline options from a Zenhub board, which must have at least one pipeline.
+///
+/// Pipelines are in board order, unless an `order` is given.
+fn pipeline_options(
+    board: Board,
+    order: Option<&[String]>,
+) -> Result<FuzzySelect<Pipeline>, Error> {
+    if board.pipelines.is_empty() {
+        return Err(no_zenhub_board_error());
+    }
+    let pipelines = match order {
+        Some(order) => order_pipelines(board.pipelines, order),
+        None => board.pipelines,
+    };
+    Ok(pipelines
+        .into_iter()
+        .map(|pipeline| (pipeline.name.clone(), pipeline))
+        .collect())
+}
+
+/// Zenhub issue metadata, cached by issue number so each issue is fetched at most once.
+#[derive(Debug, Default)]
+struct ZenhubIssueCache {
+    issues: HashMap<u32, zenhub::Issue>,
+}
+
+impl ZenhubIssueCache {
+    /// Get cached metadata for an issue number, or fetch and cache it.
+    fn get_or_fetch<F>(&mut self, issue_number: u32, fetch: F) -> Result<&zenhub::Issue, Error>
+    where
+        F: FnOnce() -> Result<zenhub::Issue, DecadogError>,
+    {
+        match self.issues.entry(issue_number) {
+            Entry::Occupied(entry) => Ok(entry.into_mut()),
+            Entry::Vacant(entry) => Ok(entry.insert(fetch()?)),
+        }
+    }
+
+    /// Get cached Zenhub metadata for an issue, or fetch it from the API.
+    fn get_zenhub_issue(
+        &mut self,
+        client: &Client<'_>,
+        repository: &Repository,
+        issue: &Issue,
+    ) -> Result<&zenhub::Issue, Error> {
+        self.get_or_fetch(issue.number, || client.get_zenhub_issue(repository, issue))
+    }
+
+    /// Record a new estimate for a cached issue.
+    fn set_estimate(&mut self, issue_number: u32, estimate: u32) {
+        if let Some(issue) = self.issues.get_mut(&issue_number) {
+            issue.estimate = Some(Estimate::from(&estimate));
+        }
     }
 }
 
 struct MilestoneManager<'a> {
-    client: &'a Client<'a>,
+    github: &'a dyn GithubApi,
+    zenhub: &'a dyn ZenhubApi,
     milestone: &'a Milestone,
+    pipeline_position: Option<&'a str>,
 
     repository: Repository,
     workspace: Workspace,
@@ -68,32 +303,93 @@ struct MilestoneManager<'a> {
 }
 
 enum LoopStatus {
-    Success,
+    Issue(u32),
     Quit,
     NextPipeline,
 }
 
+/// Issues which failed to sync, with the error encountered.
+#[derive(Debug, Default)]
+struct SyncFailures {
+    failures: Vec<(u32, String)>,
+}
+
+impl SyncFailures {
+    /// Record a failure to sync an issue.
+    fn record(&mut self, issue_number: u32, error: &Error) {
+        self.failures.push((issue_number, error.to_string()));
+    }
+
+    /// Summary of all failures, if there were any.
+    fn summary(&self) -> Option<String> {
+        if self.failures.is_empty() {
+            return None;
+        }
+        let mut summary = format!("{} issue(s) failed to sync:", self.failures.len());
+        for (issue_number, error) in &self.failures {
+            summary.push_str(&format!("\n  #{}: {}", issue_number, error));
+        }
+        Some(summary)
+    }
+
+    /// Print a summary of failures, and error if there were any.
+    fn into_result(self) -> Result<(), Error> {
+        match self.summary() {
+            None => Ok(()),
+            Some(summary) => {
+                eprintln!("{}", summary);
+                Err(Error::User {
+                    description: format!("{} issue(s) failed to sync.", self.failures.len()),
+                })
+            }
+        }
+    }
+}
+
 impl<'a> MilestoneManager<'a> {
-    fn new(client: &'a Client<'a>, milestone: &'a Milestone) -> Result<Self, Error> {
-        let organisation_members = client.get_members()?;
-        let member_options: FuzzySelect<OrganisationMember> = organisation_members
-            .into_iter()
-            .map(|member| (member.login.clone(), member))
-            .collect();
+    fn new(
+        github: &'a dyn GithubApi,
+        zenhub: &'a dyn ZenhubApi,
+        milestone: &'a Milestone,
+        pipeline_position: Option<&'a str>,
+        pipeline_order: Option<&[String]>,
+        max_concurrency: usize,
+    ) -> Result<Self, Error> {
+        // Assignees are independent of the board, so fetch them concurrently.
+        // Only users assignable in the repository are offered, as others would be rejected.
+        let (assignable_users, board) = parallel::join(
+            max_concurrency,
+            || github.get_assignable_users(),
+            || -> Result<_, Error> {
+                let repository = github.get_repository()?;
+                let workspace = zenhub.get_first_workspace(&repository)?;
 
-        let repository = client.get_repository()?;
-        let workspace = client.get_first_workspace(&repository)?;
+                let board =
+                    zenhub
+                        .get_board(&repository, &workspace)
+                        .map_err(|error| match error {
+                            DecadogError::Api { status, .. } if status.as_u16() == 404 => {
+                                no_zenhub_board_error()
+                            }
+                            error => error.into(),
+                        })?;
+                Ok((repository, workspace, board))
+            },
+        );
 
-        let board = client.get_board(&repository, &workspace)?;
-        let pipeline_options: FuzzySelect<Pipeline> = board
-            .pipelines
+        let member_options: FuzzySelect<OrganisationMember> = assignable_users?
             .into_iter()
-            .map(|pipeline| (pipeline.name.clone(), pipeline))
+            .map(|member| (member.login.clone(), member))
             .collect();
 
+        let (repository, workspace, board) = board?;
+        let pipeline_options = pipeline_options(board, pipeline_order)?;
+
         Ok(Self {
-            client,
+            github,
+            zenhub,
             milestone,
+            pipeline_position,
             repository,
             workspace,
             member_options,
@@ -101,27 +397,34 @@ impl<'a> MilestoneManager<'a> {
         })
     }
 
+    /// Sync issues until the user quits, then report any failures.
     fn manage(&self) -> Result<(), Error> {
+        let mut failures = SyncFailures::default();
         loop {
             let pipeline = self.pipeline_options.interact()?;
             loop {
-                match self.manage_issue(pipeline) {
-                    Ok(LoopStatus::Success) => continue,
+                match self.prompt_issue() {
+                    Ok(LoopStatus::Issue(issue_number)) => {
+                        if let Err(error) = self.manage_issue(issue_number, pipeline) {
+                            error!("{}", error);
+                            failures.record(issue_number, &error);
+                        }
+                    }
                     Ok(LoopStatus::NextPipeline) => break,
-                    Ok(LoopStatus::Quit) => return Ok(()),
+                    Ok(LoopStatus::Quit) => return failures.into_result(),
                     Err(error) => error!("{}", error),
                 }
             }
         }
     }
 
-    fn manage_issue(&self, pipeline: &Pipeline) -> Result<LoopStatus, Error> {
+    fn prompt_issue(&self) -> Result<LoopStatus, Error> {
         // Input an issue number
         let issue_number_str = Input::<String>::new()
             .with_prompt("Issue number (n: next pipeline, q: quit)")
             .interact()?;
 
-        // Fetch the issue and parse the number
+        // Parse the number
         if issue_number_str == "q" {
             return Ok(LoopStatus::Quit);
         } else if issue_number_str == "n" {
@@ -130,8 +433,11 @@ impl<'a> MilestoneManager<'a> {
         let issue_number = issue_number_str.parse().map_err(|_| Error::User {
             description: format!("Invalid issue number {}.", &issue_number_str),
         })?;
+        Ok(LoopStatus::Issue(issue_number))
+    }
 
-        let issue = self.client.get_issue(issue_number)?;
+    fn manage_issue(&self, issue_number: u32, pipeline: &Pipeline) -> Result<(), Error> {
+        let issue = self.github.get_issue(issue_number)?;
         eprintln!("{}", issue);
 
         // If already assigned to the target milestone, no-op
@@ -140,23 +446,14 @@ impl<'a> MilestoneManager<'a> {
         } else {
             // Otherwise, confirm the assignment
             if Confirm::new("Assign to milestone?").interact()? {
-                self.client
+                self.github
                     .assign_issue_to_milestone(&issue, Some(&self.milestone))?;
             } else {
-                return Ok(LoopStatus::Success);
+                return Ok(());
             }
         }
 
-        if issue.assigned_to(pipeline) {
-            eprintln!("Already in pipeline.");
-        } else {
-            self.client.move_issue_to_pipeline(
-                &self.repository,
-                &self.workspace,
-                &issue,
-                &pipeline,
-            )?;
-        }
+        self.move_to_pipeline(&issue, pipeline)?;
 
         let update_assignment = if issue.assignees.is_empty() {
             // If we do not have an assignee, default to updating assignment
@@ -178,12 +475,27 @@ impl<'a> MilestoneManager<'a> {
         if update_assignment {
             let organisation_member = self.member_options.interact()?;
             if !organisation_member.assigned_to(&issue) {
-                self.client
+                self.github
                     .assign_member_to_issue(&organisation_member, &issue)?;
             };
         }
 
-        Ok(LoopStatus::Success)
+        Ok(())
+    }
+
+    /// Move an issue to the pipeline, if not already in it.
+    fn move_to_pipeline(&self, issue: &Issue, pipeline: &Pipeline) -> Result<(), Error> {
+        let outcome = self.zenhub.move_issue_to_pipeline(
+            &self.repository,
+            &self.workspace,
+            issue,
+            pipeline,
+            self.pipeline_position,
+        )?;
+        if outcome == MoveOutcome::AlreadyThere {
+            eprintln!("Already in pipeline.");
+        }
+        Ok(())
     }
 }
 
@@ -205,7 +517,7 @@ fn sync_sprint(settings: &Settings) -> Result<(), Error> {
             })?
             .as_ref(),
     )?;
-    let client = Client::new(&settings.owner, &settings.repo, &github, &zenhub)?;
+    let client = settings.client(&github, &zenhub)?;
 
     // Select milestone to move tickets to
     let milestones = client.get_milestones()?;
@@ -218,11 +530,78 @@ fn sync_sprint(settings: &Settings) -> Result<(), Error> {
         Select::new("Sprint to sync", &milestones).expect("At least one milestone is required.");
     let open_milestone = select_milestone.interact()?;
 
-    let milestone_manager = MilestoneManager::new(&client, open_milestone)?;
+    let milestone_manager = MilestoneManager::new(
+        &client,
+        &client,
+        open_milestone,
+        settings.default_pipeline_position.as_deref(),
+        settings.pipeline_order.as_deref(),
+        settings.max_concurrency(),
+    )?;
     milestone_manager.manage()
 }
 
-fn create_sprint(settings: &Settings) -> Result<(), Error> {
+/// Sprint title template used if none is configured.
+const DEFAULT_SPRINT_TITLE_TEMPLATE: &str = "Sprint {number}";
+
+/// Format a sprint title, substituting the sprint number into `{number}` in the template.
+fn sprint_title(template: Option<&str>, sprint_number: &str) -> String {
+    template
+        .unwrap_or(DEFAULT_SPRINT_TITLE_TEMPLATE)
+        .replace("{number}", sprint_number)
+}
+
+/// Hour of day (UTC) sprints start and are due; the Zenhub UI uses midday, so copy that here.
+const SPRINT_START_HOUR: u32 = 12;
+
+/// Days from the start of a sprint to its due date.
+const SPRINT_LENGTH_DAYS: i64 = 13;
+
+/// Start and due dates of a sprint starting on `today` at `start_hour` UTC.
+fn compute_sprint_dates(
+    today: NaiveDate,
+    start_hour: u32,
+    length_days: i64,
+) -> (DateTime<FixedOffset>, DateTime<FixedOffset>) {
+    let start_date = DateTime::from_utc(today.and_hms(start_hour, 0, 0), FixedOffset::east(0));
+    let due_on = start_date + Duration::days(length_days);
+    (start_date, due_on)
+}
+
+/// Specification of a sprint to create.
+#[derive(Debug, Clone, PartialEq)]
+struct SprintSpec {
+    start_day: NaiveDate,
+    start_hour: u32,
+    length_days: i64,
+}
+
+impl SprintSpec {
+    /// A sprint of the default length, starting on `start_day`.
+    fn new(start_day: NaiveDate) -> Self {
+        Self {
+            start_day,
+            start_hour: SPRINT_START_HOUR,
+            length_days: SPRINT_LENGTH_DAYS,
+        }
+    }
+
+    fn length_days(&mut self, length_days: i64) -> &mut Self {
+        self.length_days = length_days;
+        self
+    }
+
+    /// Start and due dates of the sprint.
+    fn dates(&self) -> (DateTime<FixedOffset>, DateTime<FixedOffset>) {
+        compute_sprint_dates(self.start_day, self.start_hour, self.length_days)
+    }
+}
+
+fn create_sprint(
+    settings: &Settings,
+    title: Option<&str>,
+    length_days: Option<i64>,
+) -> Result<(), Error> {
     let github = github::Client::new(&settings.github_url, &settings.github_token.value())?;
     let zenhub = zenhub::Client::new(
         settings
@@ -240,34 +619,215 @@ fn create_sprint(settings: &Settings) -> Result<(), Error> {
             })?
             .as_ref(),
     )?;
-    let client = Client::new(&settings.owner, &settings.repo, &github, &zenhub)?;
+    let client = settings.client(&github, &zenhub)?;
 
-    // Select milestone to move tickets to
-    if Confirm::new("Create sprint from today for two weeks?").interact()? {
-        let sprint_number = Input::<String>::new()
-            .with_prompt("Sprint number")
-            .interact()?;
+    let length_days = length_days.unwrap_or(SPRINT_LENGTH_DAYS);
+    if Confirm::new(&format!(
+        "Create sprint from today, due in {} days?",
+        length_days
+    ))
+    .interact()?
+    {
+        let title = match title {
+            Some(title) => title.to_owned(),
+            None => {
+                let sprint_number = Input::<String>::new()
+                    .with_prompt("Sprint number")
+                    .interact()?;
+                sprint_title(settings.sprint_title_template.as_deref(), &sprint_number)
+            }
+        };
+
+        let (start_date, due_on) = SprintSpec::new(Local::today().naive_local())
+            .length_days(length_days)
+            .dates();
 
         let repository = client.get_repository()?;
-        // Zenhub UI uses dates with midday, so copy that here
-        let start_date = DateTime::from_utc(
-            Local::today().naive_local().and_hms(12, 00, 00),
-            FixedOffset::east(0),
-        );
-        let due_on = start_date + Duration::days(13);
-        let sprint = client.create_sprint(&repository, &sprint_number, start_date, due_on)?;
+        let sprint = client.create_sprint(&repository, &title, start_date, due_on)?;
 
         eprintln!("Created '{}'", sprint.milestone.title);
     }
     Ok(())
 }
 
-fn finish_sprint(settings: &Settings) -> Result<(), Error> {
+/// Longest plausible sprint, used to bound the review window.
+const MAX_SPRINT_DAYS: i64 = 28;
+
+/// Start of the window to review out of sprint issues in.
+///
+/// The sprint start date is clamped to no earlier than `MAX_SPRINT_DAYS` before the
+/// milestone is due, returning `None` if it was unchanged.
+fn clamp_review_start(
+    start_date: &DateTime<FixedOffset>,
+    due_on: &DateTime<FixedOffset>,
+) -> Option<DateTime<FixedOffset>> {
+    let earliest = *due_on - Duration::days(MAX_SPRINT_DAYS);
+    if *start_date < earliest {
+        Some(earliest)
+    } else {
+        None
+    }
+}
+
+/// Percentage by which counts of done points may differ before warning.
+const DONE_POINTS_TOLERANCE_PERCENT: u32 = 20;
+
+/// Whether two counts of done points differ by more than `DONE_POINTS_TOLERANCE_PERCENT`.
+fn done_points_disagree(done: u32, other_done: u32) -> bool {
+    let larger = done.max(other_done);
+    (larger - done.min(other_done)) * 100 > larger * DONE_POINTS_TOLERANCE_PERCENT
+}
+
+/// Warning if the points closed in the milestone disagree with those closed during the sprint.
+///
+/// Both planned and out of sprint work count towards `done_total`, so it is compared against
+/// all points closed during the sprint by `closed_at`.
+fn closed_points_warning(points: &SprintPoints, done_by_closed_at: u32) -> Option<String> {
+    if done_points_disagree(points.done_total, done_by_closed_at) {
+        Some(format!(
+            "Warning: {} points were closed in the milestone, but {} were closed during the sprint.",
+            points.done_total, done_by_closed_at
+        ))
+    } else {
+        None
+    }
+}
+
+/// Label marking issues as obsolete, used if none is configured.
+const DEFAULT_OBSOLETE_LABEL: &str = "Z-obsolete";
+
+/// Query for closed issues without a milestone, that may have been done in the sprint.
+///
+/// If `labels` are given, only issues with at least one of them are included.
+fn out_of_sprint_query(
+    start_date: &DateTime<FixedOffset>,
+    end_date: &DateTime<FixedOffset>,
+    labels: Option<&[String]>,
+    obsolete_label: &str,
+) -> SearchQueryBuilder {
+    let mut query = SearchQueryBuilder::new();
+    query
+        .no_milestone()
+        .closed_between(start_date, end_date)
+        .not_label(obsolete_label);
+    if let Some(labels) = labels {
+        query.any_label(labels);
+    }
+    query
+}
+
+/// Search for issues, excluding any pull requests.
+///
+/// Warns if the search matched more results than Github returns.
+fn search_issues(client: &Client<'_>, query: &mut SearchQueryBuilder) -> Result<Vec<Issue>, Error> {
+    let results = client.search_issues(query)?;
+    if results.hit_result_cap() {
+        eprintln!(
+            "{}",
+            format!(
+                "Warning: search matched over {} issues, so results are truncated.",
+                SEARCH_RESULT_CAP
+            )
+            .yellow()
+        );
+    }
+    Ok(github::exclude_pull_requests(results).collect::<Result<Vec<_>, _>>()?)
+}
+
+fn sprint_capacity(settings: &Settings) -> Result<(), Error> {
+    let github = github::Client::new(&settings.github_url, &settings.github_token.value())?;
+    let zenhub = zenhub::Client::new(
+        settings
+            .zenhub_url
+            .as_ref()
+            .ok_or(Error::Settings {
+                description: "Zenhub url required to show capacity.".to_owned(),
+            })?
+            .as_ref(),
+        settings
+            .zenhub_token
+            .as_ref()
+            .ok_or(Error::Settings {
+                description: "Zenhub token required to show capacity.".to_owned(),
+            })?
+            .as_ref(),
+    )?;
+    let client = settings.client(&github, &zenhub)?;
+
+    let milestones = client.get_milestones()?;
+    if milestones.is_empty() {
+        eprintln!("No open milestones.");
+        return Ok(());
+    }
+
+    let select_milestone =
+        Select::new("Sprint", &milestones).expect("At least one milestone is required.");
+    let milestone = select_milestone.interact()?;
+
+    let repository = client.get_repository()?;
+    let issues = search_issues(
+        &client,
+        SearchQueryBuilder::new()
+            .milestone_of(milestone)
+            .state(&State::Open),
+    )?;
+
+    // Estimates are independent per issue, so fetch them concurrently
+    let points = parallel::map(&issues, settings.max_concurrency(), |issue| {
+        client
+            .get_zenhub_issue(&repository, issue)
+            .map(|zenhub_issue| zenhub_issue.points())
+    })
+    .into_iter()
+    .collect::<Result<Vec<_>, _>>()?;
+    let capacity = Capacity::from_points(issues.iter().zip(points));
+
+    println!("{}", "Open points by member:".bold());
+    for (login, points) in capacity.sorted() {
+        println!("{:>4}  {}", points, login);
+    }
+    println!("{:>4}  {}", capacity.unassigned, "(unassigned)".dimmed());
+    Ok(())
+}
+
+/// Offer to close open issues labelled obsolete as not planned.
+fn close_obsolete_issues(
+    client: &Client<'_>,
+    plan: &ChangePlan,
+    obsolete_label: &str,
+) -> Result<(), Error> {
+    println!();
+    println!("{}", "Obsolete issues:".bold());
+    let obsolete_issues = search_issues(
+        client,
+        SearchQueryBuilder::new()
+            .state(&State::Open)
+            .any_label(&[obsolete_label]),
+    )?;
+    for issue in obsolete_issues.iter() {
+        println!("{} -> {}", issue, issue.html_url);
+        if Confirm::new("Close as not planned?").interact()?
+            && plan.propose(&[Change::CloseIssueNotPlanned {
+                issue: issue.number,
+            }])
+        {
+            client.close_issue_as_not_planned(issue)?;
+        }
+    }
+    Ok(())
+}
+
+fn finish_sprint(
+    settings: &Settings,
+    dry_run: bool,
+    close_obsolete: bool,
+    reestimate: bool,
+) -> Result<(), Error> {
     // To count as points in the sprint, the ticket must have been
     // - closed in the sprint period
     // - have points assigned
     //
-    // For each ticket *closed* in the sprint time range (start to *now*)
+    // For each ticket *closed* in the sprint time range (start to due date, or *now*)
     // - if it has no milestone attached, prompt to attach to open milestone
     // - if it has no points, prompt to assign estimate
     //
@@ -291,10 +851,16 @@ fn finish_sprint(settings: &Settings) -> Result<(), Error> {
             })?
             .as_ref(),
     )?;
-    let client = Client::new(&settings.owner, &settings.repo, &github, &zenhub)?;
+    let client = settings.client(&github, &zenhub)?;
 
-    let select_estimate =
-        Select::new("Estimate", ESTIMATES.iter()).expect("At least one estimate is required.");
+    let select_estimate = Select::new("Estimate", ESTIMATE_CHOICES.iter())
+        .expect("At least one estimate is required.");
+    let mut zenhub_issues = ZenhubIssueCache::default();
+    let plan = ChangePlan::new(dry_run);
+    let obsolete_label = settings
+        .obsolete_label
+        .as_deref()
+        .unwrap_or(DEFAULT_OBSOLETE_LABEL);
 
     // Select milestone to close
     let milestones = client.get_milestones()?;
@@ -310,24 +876,47 @@ fn finish_sprint(settings: &Settings) -> Result<(), Error> {
     let repository = client.get_repository()?;
     let sprint = client.get_sprint(&repository, open_milestone)?;
 
+    // Sprints still in progress are bounded by the current time
+    let now: DateTime<FixedOffset> = Local::now().into();
+    let end_date = sprint.milestone.due_on.min(now);
+
+    // Guard against a misconfigured start date pulling in months of issues
+    let start_date =
+        match clamp_review_start(&sprint.start_date.start_date, &sprint.milestone.due_on) {
+            Some(start_date) => {
+                eprintln!(
+                    "{}",
+                    format!(
+                        "Warning: sprint start {} is over {} days before due; reviewing from {}.",
+                        sprint.start_date.start_date.format("%Y-%m-%d"),
+                        MAX_SPRINT_DAYS,
+                        start_date.format("%Y-%m-%d")
+                    )
+                    .yellow()
+                );
+                start_date
+            }
+            None => sprint.start_date.start_date,
+        };
+
     println!();
     println!("{}", "Issues for review:".bold());
-    let out_of_sprint_issues = client
-        .search_issues(
-            SearchQueryBuilder::new()
-                .no_milestone()
-                .closed_on_or_after(&sprint.start_date.start_date)
-                .not_label("Z-obsolete"),
-        )?
-        .collect::<Result<Vec<_>, _>>()?;
-    let milestone_issues = client
-        .search_issues(
-            SearchQueryBuilder::new()
-                .milestone(&sprint.milestone.title)
-                .state(&State::Closed)
-                .not_label("Z-obsolete"),
-        )?
-        .collect::<Result<Vec<_>, _>>()?;
+    let out_of_sprint_issues = search_issues(
+        &client,
+        &mut out_of_sprint_query(
+            &start_date,
+            &end_date,
+            settings.review_labels.as_deref(),
+            obsolete_label,
+        ),
+    )?;
+    let milestone_issues = search_issues(
+        &client,
+        SearchQueryBuilder::new()
+            .milestone_of(&sprint.milestone)
+            .state(&State::Closed)
+            .not_label(obsolete_label),
+    )?;
 
     for issue in out_of_sprint_issues.into_iter().chain(milestone_issues) {
         // If assigned to a different milestone, ignore
@@ -337,7 +926,9 @@ fn finish_sprint(settings: &Settings) -> Result<(), Error> {
             };
         };
 
-        let zenhub_issue = client.get_zenhub_issue(&repository, &issue)?;
+        let zenhub_issue = zenhub_issues
+            .get_zenhub_issue(&client, &repository, &issue)?
+            .clone();
         // If it's an epic, ignore
         if zenhub_issue.is_epic {
             continue;
@@ -356,85 +947,147 @@ fn finish_sprint(settings: &Settings) -> Result<(), Error> {
         // If answer is no, ignore
         if issue.milestone.is_none() {
             show_description_once();
-            if Confirm::new("Assign to milestone?").interact()? {
-                client.assign_issue_to_milestone(&issue, Some(&sprint.milestone))?;
-            } else {
+            if !Confirm::new("Assign to milestone?").interact()? {
                 continue;
             }
+            if zenhub_issue.estimate == None {
+                // Assign and estimate in one step
+                let new_estimate = prompt_estimate(&select_estimate, None)?;
+                if plan.propose(&[
+                    Change::AssignMilestone {
+                        issue: issue.number,
+                        milestone: &sprint.milestone.title,
+                    },
+                    Change::Estimate {
+                        issue: issue.number,
+                        from: zenhub_issue.points(),
+                        to: new_estimate,
+                    },
+                ]) {
+                    client.finalize_issue(&repository, &issue, &sprint.milestone, new_estimate)?;
+                }
+                zenhub_issues.set_estimate(issue.number, new_estimate);
+                continue;
+            }
+            if plan.propose(&[Change::AssignMilestone {
+                issue: issue.number,
+                milestone: &sprint.milestone.title,
+            }]) {
+                client.assign_issue_to_milestone(&issue, Some(&sprint.milestone))?;
+            }
         };
 
-        if zenhub_issue.estimate == None {
-            show_description_once();
-            let new_estimate = select_estimate.interact()?;
-            client.set_estimate(&repository, &issue, new_estimate.value)?;
-        };
+        match &zenhub_issue.estimate {
+            None => {
+                show_description_once();
+                let new_estimate = prompt_estimate(&select_estimate, None)?;
+                if plan.propose(&[Change::Estimate {
+                    issue: issue.number,
+                    from: zenhub_issue.points(),
+                    to: new_estimate,
+                }]) {
+                    client.set_estimate(&repository, &issue, new_estimate)?;
+                }
+                zenhub_issues.set_estimate(issue.number, new_estimate);
+            }
+            Some(estimate) if reestimate => {
+                show_description_once();
+                if !Confirm::new(&format!("Re-estimate (currently {})?", estimate)).interact()? {
+                    continue;
+                }
+                // The current estimate is highlighted, so it is kept by default
+                let new_estimate = prompt_estimate(&select_estimate, Some(estimate))?;
+                if new_estimate == estimate.value {
+                    continue;
+                }
+                if plan.propose(&[Change::Estimate {
+                    issue: issue.number,
+                    from: estimate.value,
+                    to: new_estimate,
+                }]) {
+                    client.set_estimate(&repository, &issue, new_estimate)?;
+                }
+                zenhub_issues.set_estimate(issue.number, new_estimate);
+            }
+            Some(_) => (),
+        }
+    }
+
+    if close_obsolete {
+        close_obsolete_issues(&client, &plan, obsolete_label)?;
     }
 
     println!();
     println!("{}", "Issues open in sprint:".bold());
-    let open_milestone_issues = client
-        .search_issues(
-            SearchQueryBuilder::new()
-                .state(&State::Open)
-                .milestone(&sprint.milestone.title),
-        )?
-        .collect::<Result<Vec<_>, _>>()?;
+    let open_milestone_issues = search_issues(
+        &client,
+        SearchQueryBuilder::new()
+            .state(&State::Open)
+            .milestone_of(&sprint.milestone),
+    )?;
     for issue in open_milestone_issues.iter() {
-        println!("{}", issue);
+        println!(
+            "{}: {}",
+            issue.number,
+            issue.truncated_title(LISTING_TITLE_WIDTH)
+        );
     }
 
     println!();
-    // Update title with number of planned and completed points this sprint
-    // Prompt user for number of planned points in the sprint
-    let planned_points_str = Input::<String>::new()
-        .with_prompt("Points planned this sprint (q: quit)")
-        .interact()?;
-    if planned_points_str == "q" {
-        return Ok(());
-    }
-    let planned_points: u32 = planned_points_str.parse().map_err(|_| Error::User {
-        description: format!("Invalid number of planned points {}.", &planned_points_str),
-    })?;
-
     println!("Calucating points summary...");
     let mut points_in_milestone: u32 = 0;
     let mut points_in_milestone_open: u32 = 0;
-    let milestone_issues = client
-        .search_issues(SearchQueryBuilder::new().milestone(&sprint.milestone.title))?
-        .collect::<Result<Vec<_>, _>>()?;
-    for issue in milestone_issues.into_iter() {
-        let zenhub_issue = client.get_zenhub_issue(&repository, &issue)?;
-        let issue_estimate = match zenhub_issue.estimate {
-            Some(estimate) => estimate.value,
-            None => 0,
+    let mut completed = vec![];
+    let mut incomplete = vec![];
+    let mut estimates = HashMap::new();
+    let milestone_issues = search_issues(
+        &client,
+        SearchQueryBuilder::new().milestone_of(&sprint.milestone),
+    )?;
+    for issue in milestone_issues.iter() {
+        let zenhub_issue = zenhub_issues.get_zenhub_issue(&client, &repository, issue)?;
+        let issue_estimate = zenhub_issue.points();
+        estimates.insert(issue.number, issue_estimate);
+        let report_issue = ReportIssue {
+            number: issue.number,
+            title: issue.title.clone(),
+            points: issue_estimate,
         };
         if issue.state == State::Open {
             points_in_milestone_open += issue_estimate;
-        };
+            incomplete.push(report_issue);
+        } else {
+            completed.push(report_issue);
+        }
         points_in_milestone += issue_estimate;
     }
 
-    let sprint_points = SprintPoints::new(
-        planned_points,
-        points_in_milestone,
-        points_in_milestone_open,
-    )?;
+    // Update title with number of planned and completed points this sprint
+    // Prompt user for number of planned points in the sprint
+    let points = match prompt_sprint_points(points_in_milestone, points_in_milestone_open)? {
+        Some(points) => points,
+        None => return Ok(()),
+    };
+
+    let report = SprintReport {
+        milestone: sprint.milestone.clone(),
+        points,
+        completed,
+        incomplete,
+    };
+    let sprint_points = &report.points;
 
-    eprintln!(
-        r#"*{}* Report
----
-We completed *{}* planned points out of *{}* ({} remaining).
-We also did {} out of sprint points.
-In total, we finished *{} points* of work."#,
-        sprint.milestone.title,
-        sprint_points.done_in_sprint,
-        sprint_points.planned,
-        sprint_points.planned - sprint_points.done_in_sprint,
-        sprint_points.done_out_of_sprint,
-        sprint_points.done_total
-    );
+    eprintln!("{}", report);
     eprintln!();
 
+    // Cross-check the points closed in the milestone against when issues were actually closed
+    let done_by_closed_at =
+        compute_done_points_by_closed_at(&milestone_issues, &estimates, &start_date, &end_date);
+    if let Some(warning) = closed_points_warning(sprint_points, done_by_closed_at) {
+        eprintln!("{}", warning.yellow());
+        eprintln!();
+    }
+
     if Confirm::new("Close sprint?").interact()? {
         // New title: Sprint <milestone_number> [<points done in sprint>/<points planned> + <points
         // done out of sprint>]
@@ -445,13 +1098,27 @@ In total, we finished *{} points* of work."#,
             sprint_points.planned,
             sprint_points.done_out_of_sprint
         );
-        client.update_milestone_title(&sprint.milestone, new_title)?;
+        if plan.propose(&[Change::RenameMilestone {
+            from: &sprint.milestone.title,
+            to: &new_title,
+        }]) {
+            client.update_milestone_title(&sprint.milestone, new_title)?;
+        }
 
         println!("Closing milestone.");
-        client.close_milestone(&sprint.milestone)?;
+        if plan.propose(&[Change::CloseMilestone {
+            milestone: &sprint.milestone.title,
+        }]) {
+            client.close_milestone(&sprint.milestone)?;
+        }
         println!("Removing open issues from milestone...");
         for issue in open_milestone_issues.iter() {
-            client.assign_issue_to_milestone(&issue, None)?;
+            if plan.propose(&[Change::UnassignMilestone {
+                issue: issue.number,
+                milestone: &sprint.milestone.title,
+            }]) {
+                client.assign_issue_to_milestone(&issue, None)?;
+            }
         }
     } else {
         return Ok(());
@@ -464,21 +1131,629 @@ In total, we finished *{} points* of work."#,
 pub enum Command {
     #[structopt(name = "create")]
     /// Create a new sprint.
-    Create,
+    Create {
+        /// Milestone title, used verbatim instead of the sprint title template.
+        #[structopt(long = "title")]
+        title: Option<String>,
+
+        /// Days from the start of the sprint to its due date.
+        #[structopt(long = "length-days")]
+        length_days: Option<i64>,
+    },
 
     #[structopt(name = "sync")]
     /// Sync a physical board to the digital board.
     Sync,
 
+    #[structopt(name = "capacity")]
+    /// Show open points assigned to each member in a sprint.
+    Capacity,
+
     #[structopt(name = "finish")]
     /// Finish an open sprint.
-    Finish,
+    Finish {
+        /// Preview changes without applying them.
+        #[structopt(long = "dry-run")]
+        dry_run: bool,
+
+        /// Offer to close open issues labelled obsolete as not planned.
+        #[structopt(long = "close-obsolete")]
+        close_obsolete: bool,
+
+        /// Offer to re-estimate reviewed issues that already have an estimate.
+        #[structopt(long = "reestimate")]
+        reestimate: bool,
+    },
 }
 
 pub fn run(command: &Command, settings: &Settings) -> Result<(), Error> {
     match command {
-        Command::Create => create_sprint(settings),
+        Command::Create { title, length_days } => {
+            create_sprint(settings, title.as_deref(), *length_days)
+        }
         Command::Sync => sync_sprint(settings),
-        Command::Finish => finish_sprint(settings),
+        Command::Capacity => sprint_capacity(settings),
+        Command::Finish {
+            dry_run,
+            close_obsolete,
+            reestimate,
+        } => finish_sprint(settings, *dry_run, *close_obsolete, *reestimate),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc::{self, Receiver, Sender};
+    use std::sync::Mutex;
+    use std::time::Duration as StdDuration;
+
+    use chrono::TimeZone;
+    use decadog_core::DEFAULT_MAX_CONCURRENCY;
+
+    use super::*;
+
+    #[test]
+    fn sprint_dates() {
+        let utc = FixedOffset::east(0);
+        for (today, start_hour, length_days, start, due) in &[
+            (
+                NaiveDate::from_ymd(2020, 5, 4),
+                12,
+                13,
+                utc.ymd(2020, 5, 4).and_hms(12, 0, 0),
+                utc.ymd(2020, 5, 17).and_hms(12, 0, 0),
+            ),
+            // Across a month boundary
+            (
+                NaiveDate::from_ymd(2020, 4, 27),
+                12,
+                13,
+                utc.ymd(2020, 4, 27).and_hms(12, 0, 0),
+                utc.ymd(2020, 5, 10).and_hms(12, 0, 0),
+            ),
+            // Across a leap day and European DST change, which UTC is unaffected by
+            (
+                NaiveDate::from_ymd(2020, 2, 24),
+                9,
+                34,
+                utc.ymd(2020, 2, 24).and_hms(9, 0, 0),
+                utc.ymd(2020, 3, 29).and_hms(9, 0, 0),
+            ),
+            // Across a year boundary
+            (
+                NaiveDate::from_ymd(2020, 12, 28),
+                0,
+                6,
+                utc.ymd(2020, 12, 28).and_hms(0, 0, 0),
+                utc.ymd(2021, 1, 3).and_hms(0, 0, 0),
+            ),
+        ] {
+            assert_eq!(
+                compute_sprint_dates(*today, *start_hour, *length_days),
+                (*start, *due)
+            );
+        }
+    }
+
+    #[test]
+    fn sprint_spec_dates() {
+        let today = NaiveDate::from_ymd(2020, 5, 4);
+        let utc = FixedOffset::east(0);
+        assert_eq!(
+            SprintSpec::new(today).dates(),
+            (
+                utc.ymd(2020, 5, 4).and_hms(12, 0, 0),
+                utc.ymd(2020, 5, 17).and_hms(12, 0, 0)
+            )
+        );
+        assert_eq!(
+            SprintSpec::new(today).length_days(6).dates(),
+            (
+                utc.ymd(2020, 5, 4).and_hms(12, 0, 0),
+                utc.ymd(2020, 5, 10).and_hms(12, 0, 0)
+            )
+        );
+    }
+
+    #[test]
+    fn estimate_default_to_current() {
+        assert_eq!(estimate_default_index(None), 0);
+        assert_eq!(estimate_default_index(Some(&Estimate { value: 0 })), 0);
+        assert_eq!(estimate_default_index(Some(&Estimate { value: 5 })), 4);
+        assert_eq!(estimate_default_index(Some(&Estimate { value: 13 })), 6);
+        // Custom estimates are not on the scale, so custom is highlighted rather than zero
+        assert_eq!(
+            ESTIMATE_CHOICES[estimate_default_index(Some(&Estimate { value: 4 }))],
+            EstimateChoice::Custom
+        );
+    }
+
+    #[test]
+    fn review_start_clamped() {
+        let due_on = FixedOffset::east(0).ymd(2020, 5, 29).and_hms(12, 0, 0);
+
+        let start_date = FixedOffset::east(0).ymd(2020, 5, 15).and_hms(12, 0, 0);
+        assert_eq!(clamp_review_start(&start_date, &due_on), None);
+
+        let start_date = FixedOffset::east(0).ymd(2019, 1, 1).and_hms(12, 0, 0);
+        assert_eq!(
+            clamp_review_start(&start_date, &due_on),
+            Some(FixedOffset::east(0).ymd(2020, 5, 1).and_hms(12, 0, 0))
+        );
+    }
+
+    #[test]
+    fn done_points_tolerance() {
+        assert!(!done_points_disagree(0, 0));
+        assert!(!done_points_disagree(10, 8));
+        assert!(!done_points_disagree(8, 10));
+        assert!(done_points_disagree(10, 7));
+        assert!(done_points_disagree(0, 3));
+    }
+
+    #[test]
+    fn closed_points_include_out_of_sprint_work() {
+        // 8 planned and 3 out of sprint points are done
+        let points = SprintPoints::new(10, 13, 2).unwrap();
+
+        assert_eq!(closed_points_warning(&points, 11), None);
+        assert_eq!(
+            closed_points_warning(&points, 5).unwrap(),
+            "Warning: 11 points were closed in the milestone, but 5 were closed during the sprint."
+        );
+    }
+
+    #[test]
+    fn points_input() {
+        assert_eq!(parse_points_input("").unwrap(), PointsInput::Empty);
+        assert_eq!(parse_points_input(" \t ").unwrap(), PointsInput::Empty);
+        assert_eq!(parse_points_input("q").unwrap(), PointsInput::Quit);
+        assert_eq!(parse_points_input(" quit ").unwrap(), PointsInput::Quit);
+        assert_eq!(
+            parse_points_input(" 21\n").unwrap(),
+            PointsInput::Points(21)
+        );
+        assert!(parse_points_input("twenty").is_err());
+        assert!(parse_points_input("-3").is_err());
+    }
+
+    #[test]
+    fn sync_failures_summary() {
+        let failures = SyncFailures::default();
+        assert_eq!(failures.summary(), None);
+        assert!(failures.into_result().is_ok());
+
+        let mut failures = SyncFailures::default();
+        failures.record(
+            7,
+            &Error::User {
+                description: "Invalid estimate.".to_owned(),
+            },
+        );
+        failures.record(
+            12,
+            &Error::Settings {
+                description: "Missing token.".to_owned(),
+            },
+        );
+        assert_eq!(
+            failures.summary().unwrap(),
+            "2 issue(s) failed to sync:\n  #7: User error: Invalid estimate.\n  #12: Invalid settings: Missing token."
+        );
+        assert!(failures.into_result().is_err());
+    }
+
+    #[test]
+    fn templated_sprint_title() {
+        assert_eq!(sprint_title(None, "7"), "Sprint 7");
+        assert_eq!(
+            sprint_title(Some("Iteration {number} (platform)"), "12"),
+            "Iteration 12 (platform)"
+        );
+    }
+
+    #[test]
+    fn explicit_sprint_title() {
+        match Command::from_iter_safe(&["sprint", "create", "--title", "Q3 Planning"]).unwrap() {
+            Command::Create { title, .. } => assert_eq!(title.as_deref(), Some("Q3 Planning")),
+            command => panic!("Unexpected command {:?}", command),
+        }
+        match Command::from_iter_safe(&["sprint", "create"]).unwrap() {
+            Command::Create { title, .. } => assert_eq!(title, None),
+            command => panic!("Unexpected command {:?}", command),
+        }
+    }
+
+    #[test]
+    fn zenhub_issue_fetched_once() {
+        let mut cache = ZenhubIssueCache::default();
+        let mut fetches = 0;
+        for _ in 0..3 {
+            let issue = cache
+                .get_or_fetch(42, || {
+                    fetches += 1;
+                    Ok(zenhub::Issue {
+                        estimate: Some(Estimate { value: 3 }),
+                        is_epic: false,
+                    })
+                })
+                .unwrap();
+            assert_eq!(issue.estimate, Some(Estimate { value: 3 }));
+        }
+        assert_eq!(fetches, 1);
+
+        cache.set_estimate(42, 5);
+        let issue = cache
+            .get_or_fetch(42, || panic!("Cached issue fetched again"))
+            .unwrap();
+        assert_eq!(issue.estimate, Some(Estimate { value: 5 }));
+    }
+
+    #[test]
+    fn out_of_sprint_query_bounded() {
+        let start_date = DateTime::parse_from_rfc3339("2020-04-20T12:00:00Z").unwrap();
+        let end_date = DateTime::parse_from_rfc3339("2020-05-03T12:00:00Z").unwrap();
+        assert_eq!(
+            out_of_sprint_query(&start_date, &end_date, None, DEFAULT_OBSOLETE_LABEL).build(),
+            "no:milestone state:closed closed:2020-04-20..2020-05-03 -label:Z-obsolete"
+        );
+        assert_eq!(
+            out_of_sprint_query(
+                &start_date,
+                &end_date,
+                Some(&["team-a".to_owned(), "team-b".to_owned()][..]),
+                DEFAULT_OBSOLETE_LABEL
+            )
+            .build(),
+            r#"no:milestone state:closed closed:2020-04-20..2020-05-03 -label:Z-obsolete label:"team-a","team-b""#
+        );
+        // An empty list of labels is the same as none
+        assert_eq!(
+            out_of_sprint_query(&start_date, &end_date, Some(&[]), DEFAULT_OBSOLETE_LABEL).build(),
+            "no:milestone state:closed closed:2020-04-20..2020-05-03 -label:Z-obsolete"
+        );
+    }
+
+    #[test]
+    fn custom_estimate_validated() {
+        assert_eq!(parse_custom_estimate("21").unwrap(), 21);
+        assert_eq!(parse_custom_estimate(" 40 ").unwrap(), 40);
+        assert!(parse_custom_estimate("0").is_err());
+        assert!(parse_custom_estimate("-3").is_err());
+        assert!(parse_custom_estimate("three").is_err());
+    }
+
+    #[test]
+    fn custom_estimate_set() {
+        assert_eq!(
+            EstimateChoice::Preset(Estimate { value: 5 })
+                .value(|| panic!("Preset estimate prompted for custom value"))
+                .unwrap(),
+            5
+        );
+
+        // An off-scale estimate selects the custom choice, which takes its value from the prompt
+        let choice = &ESTIMATE_CHOICES[estimate_default_index(Some(&Estimate { value: 21 }))];
+        assert_eq!(choice, &EstimateChoice::Custom);
+        assert_eq!(choice.value(|| parse_custom_estimate(" 21\n")).unwrap(), 21);
+        assert!(choice.value(|| parse_custom_estimate("0")).is_err());
+        assert!(choice.value(|| parse_custom_estimate("lots")).is_err());
+    }
+
+    /// One end of a meeting between two concurrent calls.
+    struct Meeting {
+        arrived: Mutex<Sender<()>>,
+        other_arrived: Mutex<Receiver<()>>,
+    }
+
+    impl Meeting {
+        /// Two ends of a meeting, each of which waits for the other.
+        fn pair() -> (Self, Self) {
+            let (a_sender, a_receiver) = mpsc::channel();
+            let (b_sender, b_receiver) = mpsc::channel();
+            (
+                Self {
+                    arrived: Mutex::new(a_sender),
+                    other_arrived: Mutex::new(b_receiver),
+                },
+                Self {
+                    arrived: Mutex::new(b_sender),
+                    other_arrived: Mutex::new(a_receiver),
+                },
+            )
+        }
+
+        /// Wait for the other end, which only arrives if both calls are in flight at once.
+        fn attend(&self) -> Result<(), DecadogError> {
+            self.arrived.lock().unwrap().send(()).unwrap();
+            self.other_arrived
+                .lock()
+                .unwrap()
+                .recv_timeout(StdDuration::from_secs(5))
+                .map_err(|_| DecadogError::Unknown {
+                    description: "Calls were not made concurrently.".to_owned(),
+                })
+        }
+    }
+
+    /// In-memory Github and Zenhub, recording issues moved between pipelines.
+    struct FakeApi {
+        board: Board,
+        moves: Mutex<Vec<(u32, String)>>,
+        /// If set, fetching assignable users and the board must overlap.
+        meetings: Option<(Meeting, Meeting)>,
+    }
+
+    impl FakeApi {
+        fn new(board: Board) -> Self {
+            Self {
+                board,
+                moves: Mutex::new(vec![]),
+                meetings: None,
+            }
+        }
+    }
+
+    fn fake_issue(number: u32) -> Issue {
+        let datetime = FixedOffset::east(0).ymd(2020, 5, 1).and_hms(12, 0, 0);
+        Issue {
+            id: number,
+            number,
+            state: State::Open,
+            title: format!("Issue {}", number),
+            milestone: None,
+            assignees: vec![],
+            labels: vec![],
+            created_at: datetime,
+            updated_at: datetime,
+            closed_at: None,
+            html_url: format!("https://github.com/tommilligan/decadog/issues/{}", number),
+            issue_type: None,
+            repository_url: None,
+            pull_request: None,
+        }
+    }
+
+    impl GithubApi for FakeApi {
+        fn get_repository(&self) -> Result<Repository, DecadogError> {
+            Ok(Repository {
+                id: 1234,
+                name: "decadog".to_owned(),
+                full_name: "tommilligan/decadog".to_owned(),
+            })
+        }
+
+        fn get_issue(&self, issue_number: u32) -> Result<Issue, DecadogError> {
+            Ok(fake_issue(issue_number))
+        }
+
+        fn get_milestones(&self) -> Result<Vec<Milestone>, DecadogError> {
+            Ok(vec![])
+        }
+
+        fn get_members(&self) -> Result<Vec<OrganisationMember>, DecadogError> {
+            Ok(vec![OrganisationMember {
+                login: "tommilligan".to_owned(),
+                id: 1,
+            }])
+        }
+
+        fn get_assignable_users(&self) -> Result<Vec<OrganisationMember>, DecadogError> {
+            if let Some((meeting, _)) = &self.meetings {
+                meeting.attend()?;
+            }
+            Ok(vec![OrganisationMember {
+                login: "octocat".to_owned(),
+                id: 2,
+            }])
+        }
+
+        fn assign_issue_to_milestone(
+            &self,
+            issue: &Issue,
+            _milestone: Option<&Milestone>,
+        ) -> Result<Issue, DecadogError> {
+            Ok(issue.clone())
+        }
+
+        fn assign_member_to_issue(
+            &self,
+            _member: &OrganisationMember,
+            issue: &Issue,
+        ) -> Result<Issue, DecadogError> {
+            Ok(issue.clone())
+        }
+    }
+
+    impl ZenhubApi for FakeApi {
+        fn get_first_workspace(&self, _repository: &Repository) -> Result<Workspace, DecadogError> {
+            Ok(Workspace::default())
+        }
+
+        fn get_board(
+            &self,
+            _repository: &Repository,
+            _workspace: &Workspace,
+        ) -> Result<Board, DecadogError> {
+            if let Some((_, meeting)) = &self.meetings {
+                meeting.attend()?;
+            }
+            Ok(self.board.clone())
+        }
+
+        fn move_issue_to_pipeline(
+            &self,
+            _repository: &Repository,
+            _workspace: &Workspace,
+            issue: &Issue,
+            pipeline: &Pipeline,
+            _position: Option<&str>,
+        ) -> Result<MoveOutcome, DecadogError> {
+            if issue.assigned_to(pipeline) {
+                return Ok(MoveOutcome::AlreadyThere);
+            }
+            self.moves
+                .lock()
+                .unwrap()
+                .push((issue.number, pipeline.id.clone()));
+            Ok(MoveOutcome::Moved)
+        }
+    }
+
+    #[test]
+    fn move_to_pipeline_only_if_needed() {
+        let backlog = Pipeline {
+            id: "p1".to_owned(),
+            name: "Backlog".to_owned(),
+            issues: vec![zenhub::PipelineIssue {
+                issue_number: 1,
+                estimate: None,
+                is_epic: false,
+            }],
+        };
+        let api = FakeApi::new(Board {
+            pipelines: vec![backlog.clone()],
+        });
+        let milestone = Milestone {
+            id: 1,
+            number: 1,
+            title: "Sprint 1".to_owned(),
+            state: State::Open,
+            due_on: FixedOffset::east(0).ymd(2020, 5, 14).and_hms(12, 0, 0),
+        };
+        let manager =
+            MilestoneManager::new(&api, &api, &milestone, None, None, DEFAULT_MAX_CONCURRENCY)
+                .unwrap();
+
+        manager.move_to_pipeline(&fake_issue(1), &backlog).unwrap();
+        manager.move_to_pipeline(&fake_issue(2), &backlog).unwrap();
+
+        assert_eq!(*api.moves.lock().unwrap(), vec![(2, "p1".to_owned())]);
+    }
+
+    #[test]
+    fn manager_fetches_members_and_board_concurrently() {
+        let mut api = FakeApi::new(Board {
+            pipelines: vec![Pipeline {
+                id: "p1".to_owned(),
+                name: "Backlog".to_owned(),
+                issues: vec![],
+            }],
+        });
+        api.meetings = Some(Meeting::pair());
+        let milestone = Milestone {
+            id: 1,
+            number: 1,
+            title: "Sprint 1".to_owned(),
+            state: State::Open,
+            due_on: FixedOffset::east(0).ymd(2020, 5, 14).and_hms(12, 0, 0),
+        };
+
+        // Members are only returned once the board is being fetched, and vice versa
+        assert!(
+            MilestoneManager::new(&api, &api, &milestone, None, None, DEFAULT_MAX_CONCURRENCY)
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn members_offered_are_assignable() {
+        let api = FakeApi::new(Board {
+            pipelines: vec![Pipeline {
+                id: "p1".to_owned(),
+                name: "Backlog".to_owned(),
+                issues: vec![],
+            }],
+        });
+        let milestone = Milestone {
+            id: 1,
+            number: 1,
+            title: "Sprint 1".to_owned(),
+            state: State::Open,
+            due_on: FixedOffset::east(0).ymd(2020, 5, 14).and_hms(12, 0, 0),
+        };
+        let manager =
+            MilestoneManager::new(&api, &api, &milestone, None, None, DEFAULT_MAX_CONCURRENCY)
+                .unwrap();
+
+        assert_eq!(manager.member_options.keys(), vec!["octocat"]);
+    }
+
+    #[test]
+    fn capacity_by_member() {
+        let member = |login: &str| OrganisationMember {
+            login: login.to_owned(),
+            id: 1,
+        };
+        let mut solo = fake_issue(1);
+        solo.assignees = vec![member("alice")];
+        let mut pair = fake_issue(2);
+        pair.assignees = vec![member("alice"), member("bob")];
+        let mut other = fake_issue(3);
+        other.assignees = vec![member("carol")];
+        let unassigned = fake_issue(4);
+
+        let capacity =
+            Capacity::from_points(vec![(&solo, 3), (&pair, 5), (&other, 5), (&unassigned, 2)]);
+
+        assert_eq!(
+            capacity.sorted(),
+            vec![("alice", 8), ("bob", 5), ("carol", 5)]
+        );
+        assert_eq!(capacity.unassigned, 2);
+    }
+
+    #[test]
+    fn milestone_manager_requires_board() {
+        let api = FakeApi::new(Board::default());
+        let milestone = Milestone {
+            id: 1,
+            number: 1,
+            title: "Sprint 1".to_owned(),
+            state: State::Open,
+            due_on: FixedOffset::east(0).ymd(2020, 5, 14).and_hms(12, 0, 0),
+        };
+        assert!(
+            MilestoneManager::new(&api, &api, &milestone, None, None, DEFAULT_MAX_CONCURRENCY)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn pipelines_ordered() {
+        let pipeline = |name: &str| Pipeline {
+            id: name.to_lowercase(),
+            name: name.to_owned(),
+            issues: vec![],
+        };
+        let pipelines = vec![
+            pipeline("New Issues"),
+            pipeline("Backlog"),
+            pipeline("In Progress"),
+            pipeline("Review"),
+            pipeline("Done"),
+        ];
+        let order = vec![
+            "In Progress".to_owned(),
+            "Done".to_owned(),
+            "Not On Board".to_owned(),
+        ];
+
+        let names = order_pipelines(pipelines, &order)
+            .into_iter()
+            .map(|pipeline| pipeline.name)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            vec!["In Progress", "Done", "New Issues", "Backlog", "Review"]
+        );
+    }
+
+    #[test]
+    fn empty_board_is_user_error() {
+        match pipeline_options(Board::default(), None) {
+            Err(Error::User { description }) => assert!(description.contains("configure Zenhub")),
+            _ => panic!("Expected user error for empty board"),
+        }
     }
 }
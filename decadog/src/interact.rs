@@ -25,7 +25,7 @@ impl<V> FuzzySelect<V> {
         self.lookup.get(key)
     }
 
-    fn keys(&self) -> Vec<&str> {
+    pub fn keys(&self) -> Vec<&str> {
         self.lookup.keys().map(|key| &**key).collect()
     }
 }
@@ -65,9 +65,14 @@ impl<'a, V> Select<'a, V> {
     }
 
     pub fn interact(&self) -> Result<&V, Error> {
+        self.interact_with_default(0)
+    }
+
+    /// Interact, with the option at index `default` initially highlighted.
+    pub fn interact_with_default(&self, default: usize) -> Result<&V, Error> {
         let selection_index = dialoguer::Select::new()
             .with_prompt(self.prompt)
-            .default(0)
+            .default(default)
             .items(&self.lookup.keys().collect::<Vec<&String>>())
             .interact()?;
 
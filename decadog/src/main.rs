@@ -1,8 +1,15 @@
 #![deny(clippy::all)]
 
-use std::path::PathBuf;
+use std::env;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::process;
+
+use config::{ConfigError, FileFormat};
 
 use decadog_core::secret::Secret;
+use decadog_core::zenhub::{self, PIPELINE_POSITIONS};
+use decadog_core::{github, Client, DEFAULT_MAX_CONCURRENCY};
 #[cfg(feature = "config_keyring")]
 use keyring::Keyring;
 use log::{debug, error};
@@ -13,9 +20,11 @@ mod args;
 mod command;
 mod error;
 mod interact;
+mod parallel;
+mod plan;
 
 use args::{Args, Command};
-use command::sprint;
+use command::{board, doctor, issues, sprint};
 pub use error::Error;
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
@@ -27,21 +36,103 @@ pub struct Settings {
     github_token: Secret,
     zenhub_url: Option<String>,
     zenhub_token: Option<Secret>,
+    sprint_title_template: Option<String>,
+    review_labels: Option<Vec<String>>,
+    default_pipeline_position: Option<String>,
+    obsolete_label: Option<String>,
+    pipeline_order: Option<Vec<String>>,
+    max_concurrency: Option<usize>,
+}
+
+/// Config file format, inferred from the file extension.
+fn config_file_format(config_path: &Path) -> Result<FileFormat, ConfigError> {
+    let extension = config_path
+        .extension()
+        .and_then(OsStr::to_str)
+        .map(str::to_lowercase);
+    match extension.as_deref() {
+        Some("toml") => Ok(FileFormat::Toml),
+        Some("yaml") | Some("yml") => Ok(FileFormat::Yaml),
+        Some("json") => Ok(FileFormat::Json),
+        _ => Err(ConfigError::Message(format!(
+            "Unsupported config file format for {}; use .toml, .yaml, .yml or .json",
+            config_path.display()
+        ))),
+    }
+}
+
+/// Config file names looked for in the working directory, most preferred first.
+const CONFIG_FILE_NAMES: &[&str] = &[
+    "decadog.yml",
+    "decadog.yaml",
+    "decadog.toml",
+    "decadog.json",
+];
+
+/// The most preferred config file in `dir`, if there is one.
+fn find_config_file(dir: &Path) -> Option<PathBuf> {
+    CONFIG_FILE_NAMES
+        .iter()
+        .map(|file_name| dir.join(file_name))
+        .find(|config_path| config_path.is_file())
+}
+
+/// Merge settings from the config file at `config_path`, which must exist if given.
+///
+/// Otherwise, the most preferred config file in the working directory is merged, if any.
+fn merge_config_file(
+    settings: &mut config::Config,
+    config_path: Option<PathBuf>,
+) -> Result<(), ConfigError> {
+    if let Some(config_path) = config_path.or_else(|| find_config_file(Path::new("."))) {
+        let format = config_file_format(&config_path)?;
+        settings.merge(
+            config::File::from(config_path)
+                .format(format)
+                .required(true),
+        )?;
+    }
+    Ok(())
+}
+
+/// Conventional environment variables for tokens, used if no other source sets them.
+const UNPREFIXED_TOKENS: &[(&str, &str)] = &[
+    ("github_token", "GITHUB_TOKEN"),
+    ("zenhub_token", "ZENHUB_TOKEN"),
+];
+
+/// Set any unset tokens from their conventional, unprefixed environment variables.
+fn merge_unprefixed_tokens<F>(settings: &mut config::Config, lookup: F) -> Result<(), ConfigError>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    for (key, variable) in UNPREFIXED_TOKENS {
+        if settings.get_str(key).is_err() {
+            if let Some(token) = lookup(variable) {
+                debug!("Loading {} from {}", key, variable);
+                settings.set(key, token)?;
+            }
+        }
+    }
+    Ok(())
 }
 
 impl Settings {
     /// Load settings. If a `config_path` is given, it must exist.
-    pub fn load(config_path: Option<PathBuf>) -> Result<Self, config::ConfigError> {
+    ///
+    /// Sources are applied in increasing order of precedence:
+    /// - config file
+    /// - `DECADOG_` prefixed environment variables
+    /// - OS keyring (tokens only)
+    ///
+    /// Finally, tokens still unset fall back to `GITHUB_TOKEN` and `ZENHUB_TOKEN`.
+    pub fn load(config_path: Option<PathBuf>) -> Result<Self, ConfigError> {
         debug!("Loading settings");
 
         let mut settings = config::Config::default();
         settings.set_default("github_url", "https://api.github.com/")?;
         settings.set_default("zenhub_url", "https://api.zenhub.io/")?;
-        if let Some(config_path) = config_path {
-            settings.merge(config::File::from(config_path).required(true))?;
-        } else {
-            settings.merge(config::File::with_name("decadog").required(false))?;
-        }
+        merge_config_file(&mut settings, config_path)?;
         settings.merge(config::Environment::with_prefix("DECADOG"))?;
 
         #[cfg(feature = "config_keyring")]
@@ -61,17 +152,62 @@ impl Settings {
             };
         }
 
+        merge_unprefixed_tokens(&mut settings, |variable| env::var(variable).ok())?;
+
         // Print out our settings (as a HashMap)
         let settings = settings.try_into::<Self>()?;
         debug!("Loaded settings: {:?}", settings);
+        settings.validate()?;
         Ok(settings)
     }
+
+    /// Maximum number of concurrent requests made by bulk operations.
+    fn max_concurrency(&self) -> usize {
+        self.max_concurrency.unwrap_or(DEFAULT_MAX_CONCURRENCY)
+    }
+
+    /// Create a client for the configured repository, respecting `max_concurrency`.
+    fn client<'a>(
+        &'a self,
+        github: &'a github::Client,
+        zenhub: &'a zenhub::Client,
+    ) -> Result<Client<'a>, Error> {
+        let mut client = Client::new(&self.owner, &self.repo, github, zenhub)?;
+        client.set_max_concurrency(self.max_concurrency());
+        Ok(client)
+    }
+
+    /// Check setting values that cannot be enforced by their type.
+    fn validate(&self) -> Result<(), ConfigError> {
+        if let Some(position) = &self.default_pipeline_position {
+            if !PIPELINE_POSITIONS.contains(&position.as_str()) {
+                return Err(ConfigError::Message(format!(
+                    "Invalid default_pipeline_position '{}'; use one of: {}",
+                    position,
+                    PIPELINE_POSITIONS.join(", ")
+                )));
+            }
+        }
+        if self.max_concurrency == Some(0) {
+            return Err(ConfigError::Message(
+                "Invalid max_concurrency 0; must be at least 1".to_owned(),
+            ));
+        }
+        Ok(())
+    }
 }
 
 fn run(args: Args) -> Result<(), Error> {
+    if args.no_color || env::var_os("NO_COLOR").is_some() {
+        colored::control::set_override(false);
+    }
+
     let settings = Settings::load(args.config)?;
 
     match args.command {
+        Command::Board { ref command } => board::run(command, &settings),
+        Command::Doctor => doctor::run(&settings),
+        Command::Issues { ref command } => issues::run(command, &settings),
         Command::Sprint { ref command } => sprint::run(command, &settings),
     }
 }
@@ -83,5 +219,153 @@ pub fn main() {
     let args = Args::from_args();
     if let Err(error) = run(args) {
         error!("{}", error);
+        process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    fn base_config() -> config::Config {
+        let mut settings = config::Config::default();
+        settings.set("owner", "tommilligan").unwrap();
+        settings.set("repo", "decadog").unwrap();
+        settings
+            .set("github_url", "https://api.github.com/")
+            .unwrap();
+        settings
+    }
+
+    fn mock_env(variable: &str) -> Option<String> {
+        match variable {
+            "GITHUB_TOKEN" => Some("unprefixed_token".to_owned()),
+            _ => None,
+        }
+    }
+
+    /// Settings from a config file alone, independent of the environment and keyring.
+    fn load_from(file_name: &str, contents: &str) -> Settings {
+        let config_path = env::temp_dir().join(format!("{}_{}", process::id(), file_name));
+        fs::write(&config_path, contents).unwrap();
+        let mut settings = config::Config::default();
+        settings
+            .set_default("github_url", "https://api.github.com/")
+            .unwrap();
+        let merged = merge_config_file(&mut settings, Some(config_path.clone()));
+        fs::remove_file(&config_path).unwrap();
+        merged.unwrap();
+        settings.try_into().unwrap()
+    }
+
+    #[test]
+    fn load_config_formats() {
+        let yaml = "owner: tommilligan\nrepo: decadog\ngithub_token: config_token\n";
+        let toml = "owner = \"tommilligan\"\nrepo = \"decadog\"\ngithub_token = \"config_token\"\n";
+        let json = r#"{"owner": "tommilligan", "repo": "decadog", "github_token": "config_token"}"#;
+        for (file_name, contents) in &[
+            ("decadog.yml", yaml),
+            ("decadog.yaml", yaml),
+            ("decadog.toml", toml),
+            ("decadog.json", json),
+        ] {
+            let settings = load_from(file_name, contents);
+            assert_eq!(settings.owner, "tommilligan");
+            assert_eq!(settings.repo, "decadog");
+            assert_eq!(settings.github_token.value(), "config_token");
+        }
+    }
+
+    #[test]
+    fn find_preferred_config_file() {
+        let dir = env::temp_dir().join(format!("{}_find_config_file", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        assert_eq!(find_config_file(&dir), None);
+
+        fs::write(dir.join("decadog.json"), "{}").unwrap();
+        assert_eq!(find_config_file(&dir), Some(dir.join("decadog.json")));
+        fs::write(dir.join("decadog.toml"), "").unwrap();
+        assert_eq!(find_config_file(&dir), Some(dir.join("decadog.toml")));
+        fs::write(dir.join("decadog.yml"), "").unwrap();
+        assert_eq!(find_config_file(&dir), Some(dir.join("decadog.yml")));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn unsupported_config_format() {
+        assert!(config_file_format(Path::new("decadog.ini")).is_err());
+        assert!(config_file_format(Path::new("decadog")).is_err());
+        assert_eq!(
+            config_file_format(Path::new("decadog.JSON")).unwrap(),
+            FileFormat::Json
+        );
+    }
+
+    #[test]
+    fn validate_default_pipeline_position() {
+        let mut settings = base_config();
+        settings.set("github_token", "configured_token").unwrap();
+        let settings = settings.try_into::<Settings>().unwrap();
+        assert!(settings.validate().is_ok());
+
+        for (position, valid) in &[("top", true), ("bottom", true), ("middle", false)] {
+            let mut settings = settings.clone();
+            settings.default_pipeline_position = Some((*position).to_owned());
+            assert_eq!(settings.validate().is_ok(), *valid);
+        }
+    }
+
+    #[test]
+    fn validate_max_concurrency() {
+        let mut settings = base_config();
+        settings.set("github_token", "configured_token").unwrap();
+        let settings = settings.try_into::<Settings>().unwrap();
+        assert_eq!(settings.max_concurrency(), DEFAULT_MAX_CONCURRENCY);
+
+        for (max_concurrency, valid) in &[(1, true), (8, true), (0, false)] {
+            let mut settings = settings.clone();
+            settings.max_concurrency = Some(*max_concurrency);
+            assert_eq!(settings.validate().is_ok(), *valid);
+        }
+    }
+
+    #[test]
+    fn client_max_concurrency() {
+        let github = github::Client::new("https://api.github.com/", "github_token").unwrap();
+        let zenhub = zenhub::Client::new("https://api.zenhub.com/", "zenhub_token").unwrap();
+        let mut settings = base_config();
+        settings.set("github_token", "configured_token").unwrap();
+        let mut settings = settings.try_into::<Settings>().unwrap();
+
+        assert_eq!(
+            settings.client(&github, &zenhub).unwrap().max_concurrency(),
+            DEFAULT_MAX_CONCURRENCY
+        );
+        settings.max_concurrency = Some(2);
+        assert_eq!(
+            settings.client(&github, &zenhub).unwrap().max_concurrency(),
+            2
+        );
+    }
+
+    #[test]
+    fn unprefixed_token_fallback() {
+        let mut settings = base_config();
+        merge_unprefixed_tokens(&mut settings, mock_env).unwrap();
+        let settings = settings.try_into::<Settings>().unwrap();
+        assert_eq!(settings.github_token.value(), "unprefixed_token");
+        assert_eq!(settings.zenhub_token, None);
+    }
+
+    #[test]
+    fn unprefixed_token_lowest_precedence() {
+        let mut settings = base_config();
+        settings.set("github_token", "configured_token").unwrap();
+        merge_unprefixed_tokens(&mut settings, mock_env).unwrap();
+        let settings = settings.try_into::<Settings>().unwrap();
+        assert_eq!(settings.github_token.value(), "configured_token");
     }
 }
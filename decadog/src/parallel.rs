@@ -0,0 +1,138 @@
+use std::panic;
+use std::thread;
+
+/// Run two closures concurrently, returning both results.
+///
+/// `a` is run on a new scoped thread, while `b` is run on the current thread. If
+/// `max_concurrency` is 1, `a` and then `b` are run on the current thread instead.
+pub fn join<A, B, RA, RB>(max_concurrency: usize, a: A, b: B) -> (RA, RB)
+where
+    A: FnOnce() -> RA + Send,
+    B: FnOnce() -> RB,
+    RA: Send,
+{
+    if max_concurrency <= 1 {
+        let a = a();
+        return (a, b());
+    }
+    thread::scope(|scope| {
+        let a = scope.spawn(a);
+        let b = b();
+        let a = a
+            .join()
+            .unwrap_or_else(|payload| panic::resume_unwind(payload));
+        (a, b)
+    })
+}
+
+/// Apply `f` to each item concurrently, returning results in the order of `items`.
+///
+/// Items are split into at most `max_concurrency` contiguous chunks, each processed on its
+/// own scoped thread.
+pub fn map<T, R, F>(items: &[T], max_concurrency: usize, f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    if items.is_empty() {
+        return vec![];
+    }
+    let chunk_size = items.len().div_ceil(max_concurrency.max(1));
+    let f = &f;
+    thread::scope(|scope| {
+        let handles = items
+            .chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || chunk.iter().map(f).collect::<Vec<_>>()))
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .flat_map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|payload| panic::resume_unwind(payload))
+            })
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::mpsc;
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn join_overlaps() {
+        let (a_sender, a_receiver) = mpsc::channel();
+        let (b_sender, b_receiver) = mpsc::channel();
+        let timeout = Duration::from_secs(5);
+
+        // Each side can only see the other's message if both are running at once
+        let (a, b) = join(
+            2,
+            move || {
+                a_sender.send(()).unwrap();
+                b_receiver.recv_timeout(timeout).is_ok()
+            },
+            move || {
+                b_sender.send(()).unwrap();
+                a_receiver.recv_timeout(timeout).is_ok()
+            },
+        );
+
+        assert!(a);
+        assert!(b);
+    }
+
+    #[test]
+    fn join_in_order_when_limited() {
+        let calls = Mutex::new(vec![]);
+        let caller = thread::current().id();
+
+        join(
+            1,
+            || calls.lock().unwrap().push(("a", thread::current().id())),
+            || calls.lock().unwrap().push(("b", thread::current().id())),
+        );
+
+        // Both run on the current thread, one after the other
+        assert_eq!(
+            calls.into_inner().unwrap(),
+            vec![("a", caller), ("b", caller)]
+        );
+    }
+
+    #[test]
+    fn map_preserves_order() {
+        let items = (0..10).collect::<Vec<u32>>();
+        assert_eq!(
+            map(&items, 4, |item| item * 2),
+            vec![0, 2, 4, 6, 8, 10, 12, 14, 16, 18]
+        );
+        assert_eq!(map(&[] as &[u32], 4, |item| item * 2), vec![]);
+    }
+
+    #[test]
+    fn map_limits_concurrency() {
+        let in_flight = AtomicUsize::new(0);
+        let max_in_flight = AtomicUsize::new(0);
+        let items = (0..12).collect::<Vec<u32>>();
+
+        map(&items, 3, |_| {
+            let current = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            max_in_flight.fetch_max(current, Ordering::SeqCst);
+            thread::sleep(Duration::from_millis(10));
+            in_flight.fetch_sub(1, Ordering::SeqCst);
+        });
+
+        // Calls overlap, but never more than the limit
+        let max_in_flight = max_in_flight.load(Ordering::SeqCst);
+        assert!(max_in_flight > 1);
+        assert!(max_in_flight <= 3);
+        assert_eq!(in_flight.load(Ordering::SeqCst), 0);
+    }
+}
@@ -0,0 +1,130 @@
+use colored::{ColoredString, Colorize};
+
+/// A planned change to Github or Zenhub state.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change<'a> {
+    AssignMilestone { issue: u32, milestone: &'a str },
+    UnassignMilestone { issue: u32, milestone: &'a str },
+    Estimate { issue: u32, from: u32, to: u32 },
+    RenameMilestone { from: &'a str, to: &'a str },
+    CloseMilestone { milestone: &'a str },
+    CloseIssueNotPlanned { issue: u32 },
+}
+
+impl<'a> Change<'a> {
+    /// Describe the change as a single, uncolored diff line.
+    pub fn diff_line(&self) -> String {
+        match self {
+            Change::AssignMilestone { issue, milestone } => {
+                format!("+ assign #{} to {}", issue, milestone)
+            }
+            Change::UnassignMilestone { issue, milestone } => {
+                format!("- unassign #{} from {}", issue, milestone)
+            }
+            Change::Estimate { issue, from, to } => {
+                format!("~ estimate #{} {} -> {}", issue, from, to)
+            }
+            Change::RenameMilestone { from, to } => format!("~ rename {} -> {}", from, to),
+            Change::CloseMilestone { milestone } => format!("~ close {}", milestone),
+            Change::CloseIssueNotPlanned { issue } => {
+                format!("- close #{} as not planned", issue)
+            }
+        }
+    }
+
+    /// Colorize the diff line: green for additions, red for removals, yellow for modifications.
+    pub fn render(&self) -> ColoredString {
+        let line = self.diff_line();
+        match self {
+            Change::AssignMilestone { .. } => line.as_str().green(),
+            Change::UnassignMilestone { .. } | Change::CloseIssueNotPlanned { .. } => {
+                line.as_str().red()
+            }
+            Change::Estimate { .. }
+            | Change::RenameMilestone { .. }
+            | Change::CloseMilestone { .. } => line.as_str().yellow(),
+        }
+    }
+}
+
+/// Previews planned changes, and decides whether they should be applied.
+#[derive(Debug, Clone, Default)]
+pub struct ChangePlan {
+    dry_run: bool,
+}
+
+impl ChangePlan {
+    pub fn new(dry_run: bool) -> Self {
+        Self { dry_run }
+    }
+
+    /// Print planned changes. Returns `true` if they should be applied.
+    pub fn propose(&self, changes: &[Change]) -> bool {
+        for change in changes {
+            println!("{}", change.render());
+        }
+        !self.dry_run
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diff_lines() {
+        assert_eq!(
+            Change::AssignMilestone {
+                issue: 42,
+                milestone: "Sprint 7"
+            }
+            .diff_line(),
+            "+ assign #42 to Sprint 7"
+        );
+        assert_eq!(
+            Change::UnassignMilestone {
+                issue: 42,
+                milestone: "Sprint 7"
+            }
+            .diff_line(),
+            "- unassign #42 from Sprint 7"
+        );
+        assert_eq!(
+            Change::Estimate {
+                issue: 42,
+                from: 0,
+                to: 3
+            }
+            .diff_line(),
+            "~ estimate #42 0 -> 3"
+        );
+        assert_eq!(
+            Change::RenameMilestone {
+                from: "Sprint 7",
+                to: "Sprint 7 [8/10 + 2]"
+            }
+            .diff_line(),
+            "~ rename Sprint 7 -> Sprint 7 [8/10 + 2]"
+        );
+        assert_eq!(
+            Change::CloseMilestone {
+                milestone: "Sprint 7"
+            }
+            .diff_line(),
+            "~ close Sprint 7"
+        );
+        assert_eq!(
+            Change::CloseIssueNotPlanned { issue: 42 }.diff_line(),
+            "- close #42 as not planned"
+        );
+    }
+
+    #[test]
+    fn dry_run_plan_not_applied() {
+        let change = Change::CloseMilestone {
+            milestone: "Sprint 7",
+        };
+        assert!(ChangePlan::new(false).propose(&[change.clone()]));
+        assert!(!ChangePlan::new(true).propose(&[change]));
+    }
+}
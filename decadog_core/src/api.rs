@@ -0,0 +1,118 @@
+use crate::error::Error;
+use crate::github::{Issue, Milestone, OrganisationMember, Repository};
+use crate::zenhub::{Board, MoveOutcome, Pipeline, Workspace};
+use crate::Client;
+
+/// Github operations for the configured repository.
+///
+/// Implemented by `Client`, and by in-memory fakes in tests.
+pub trait GithubApi: Sync {
+    /// Get the repository.
+    fn get_repository(&self) -> Result<Repository, Error>;
+
+    /// Get an issue by number.
+    fn get_issue(&self, issue_number: u32) -> Result<Issue, Error>;
+
+    /// Get open milestones.
+    fn get_milestones(&self) -> Result<Vec<Milestone>, Error>;
+
+    /// Get members of the repository owner's organisation.
+    fn get_members(&self) -> Result<Vec<OrganisationMember>, Error>;
+
+    /// Get users who can be assigned to issues in the repository.
+    fn get_assignable_users(&self) -> Result<Vec<OrganisationMember>, Error>;
+
+    /// Assign an issue to a milestone. Passing `None` will set to no milestone.
+    fn assign_issue_to_milestone(
+        &self,
+        issue: &Issue,
+        milestone: Option<&Milestone>,
+    ) -> Result<Issue, Error>;
+
+    /// Assign an organisation member to an issue, replacing any existing assignees.
+    fn assign_member_to_issue(
+        &self,
+        member: &OrganisationMember,
+        issue: &Issue,
+    ) -> Result<Issue, Error>;
+}
+
+/// Zenhub operations for the configured repository.
+///
+/// Implemented by `Client`, and by in-memory fakes in tests.
+pub trait ZenhubApi: Sync {
+    /// Get the first Zenhub workspace for a repository.
+    fn get_first_workspace(&self, repository: &Repository) -> Result<Workspace, Error>;
+
+    /// Get the Zenhub board for a repository.
+    fn get_board(&self, repository: &Repository, workspace: &Workspace) -> Result<Board, Error>;
+
+    /// Move an issue to a Zenhub pipeline, at `position` or the top if not given.
+    fn move_issue_to_pipeline(
+        &self,
+        repository: &Repository,
+        workspace: &Workspace,
+        issue: &Issue,
+        pipeline: &Pipeline,
+        position: Option<&str>,
+    ) -> Result<MoveOutcome, Error>;
+}
+
+impl GithubApi for Client<'_> {
+    fn get_repository(&self) -> Result<Repository, Error> {
+        Client::get_repository(self)
+    }
+
+    fn get_issue(&self, issue_number: u32) -> Result<Issue, Error> {
+        Client::get_issue(self, issue_number)
+    }
+
+    fn get_milestones(&self) -> Result<Vec<Milestone>, Error> {
+        Client::get_milestones(self)
+    }
+
+    fn get_members(&self) -> Result<Vec<OrganisationMember>, Error> {
+        Client::get_members(self)
+    }
+
+    fn get_assignable_users(&self) -> Result<Vec<OrganisationMember>, Error> {
+        Client::get_assignable_users(self)
+    }
+
+    fn assign_issue_to_milestone(
+        &self,
+        issue: &Issue,
+        milestone: Option<&Milestone>,
+    ) -> Result<Issue, Error> {
+        Client::assign_issue_to_milestone(self, issue, milestone)
+    }
+
+    fn assign_member_to_issue(
+        &self,
+        member: &OrganisationMember,
+        issue: &Issue,
+    ) -> Result<Issue, Error> {
+        Client::assign_member_to_issue(self, member, issue)
+    }
+}
+
+impl ZenhubApi for Client<'_> {
+    fn get_first_workspace(&self, repository: &Repository) -> Result<Workspace, Error> {
+        Client::get_first_workspace(self, repository)
+    }
+
+    fn get_board(&self, repository: &Repository, workspace: &Workspace) -> Result<Board, Error> {
+        Client::get_board(self, repository, workspace)
+    }
+
+    fn move_issue_to_pipeline(
+        &self,
+        repository: &Repository,
+        workspace: &Workspace,
+        issue: &Issue,
+        pipeline: &Pipeline,
+        position: Option<&str>,
+    ) -> Result<MoveOutcome, Error> {
+        Client::move_issue_to_pipeline(self, repository, workspace, issue, pipeline, position)
+    }
+}
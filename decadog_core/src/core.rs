@@ -1,3 +1,10 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use chrono::{DateTime, FixedOffset};
+use serde_derive::Serialize;
+
+use crate::error::Error;
 use crate::github::{Issue, Milestone, OrganisationMember};
 use crate::zenhub::{Pipeline, StartDate};
 
@@ -15,6 +22,110 @@ pub struct Sprint {
     pub start_date: StartDate,
 }
 
+/// Points planned and completed in a sprint.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SprintPoints {
+    pub planned: u32,
+    pub in_milestone: u32,
+    pub in_milestone_open: u32,
+
+    pub done_in_sprint: u32,
+    pub done_out_of_sprint: u32,
+    pub done_total: u32,
+}
+
+impl SprintPoints {
+    pub fn new(planned: u32, in_milestone: u32, in_milestone_open: u32) -> Result<Self, Error> {
+        let done_in_sprint =
+            planned
+                .checked_sub(in_milestone_open)
+                .ok_or_else(|| Error::Points {
+                    description: format!(
+                        "Planned points too low: {} points are still open in the milestone.",
+                        in_milestone_open
+                    ),
+                })?;
+        let done_out_of_sprint =
+            in_milestone
+                .checked_sub(planned)
+                .ok_or_else(|| Error::Points {
+                    description: format!(
+                        "Planned points too high: there are only {} points in the milestone.",
+                        in_milestone
+                    ),
+                })?;
+        let done_total = done_in_sprint + done_out_of_sprint;
+
+        Ok(Self {
+            planned,
+            in_milestone,
+            in_milestone_open,
+
+            done_in_sprint,
+            done_out_of_sprint,
+            done_total,
+        })
+    }
+}
+
+/// Total points of issues closed between `start` and `end`, inclusive, by their `closed_at` time.
+///
+/// `estimates` maps issue numbers to points; issues without an estimate count for nothing.
+/// Unlike `SprintPoints`, this does not depend on the number of points planned.
+pub fn compute_done_points_by_closed_at(
+    issues: &[Issue],
+    estimates: &HashMap<u32, u32>,
+    start: &DateTime<FixedOffset>,
+    end: &DateTime<FixedOffset>,
+) -> u32 {
+    issues
+        .iter()
+        .filter(|issue| match &issue.closed_at {
+            Some(closed_at) => closed_at >= start && closed_at <= end,
+            None => false,
+        })
+        .map(|issue| estimates.get(&issue.number).copied().unwrap_or(0))
+        .sum()
+}
+
+/// An issue as listed in a sprint report.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ReportIssue {
+    pub number: u32,
+    pub title: String,
+    pub points: u32,
+}
+
+/// Summary of a finished sprint, from which all report formats are rendered.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SprintReport {
+    pub milestone: Milestone,
+    pub points: SprintPoints,
+    /// Issues closed in the milestone.
+    pub completed: Vec<ReportIssue>,
+    /// Issues still open in the milestone.
+    pub incomplete: Vec<ReportIssue>,
+}
+
+impl fmt::Display for SprintReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            r#"*{}* Report
+---
+We completed *{}* planned points out of *{}* ({} remaining).
+We also did {} out of sprint points.
+In total, we finished *{} points* of work."#,
+            self.milestone.title,
+            self.points.done_in_sprint,
+            self.points.planned,
+            self.points.planned - self.points.done_in_sprint,
+            self.points.done_out_of_sprint,
+            self.points.done_total
+        )
+    }
+}
+
 impl AssignedTo<Milestone> for Issue {
     fn assigned_to(&self, assignable: &Milestone) -> bool {
         if let Some(issue_milestone) = &self.milestone {
@@ -46,8 +157,9 @@ impl AssignedTo<Issue> for OrganisationMember {
 
 #[cfg(test)]
 mod tests {
-    use chrono::{DateTime, FixedOffset, NaiveDateTime};
+    use chrono::{NaiveDateTime, TimeZone};
     use lazy_static::lazy_static;
+    use serde_json::json;
 
     use super::*;
 
@@ -70,6 +182,9 @@ mod tests {
                 updated_at: *DEFAULT_DATETIME_FIXED,
                 closed_at: Some(*DEFAULT_DATETIME_FIXED),
                 html_url: Default::default(),
+                issue_type: Default::default(),
+                repository_url: Default::default(),
+                pull_request: Default::default(),
             }
         }
     }
@@ -86,6 +201,128 @@ mod tests {
         }
     }
 
+    fn mock_report() -> SprintReport {
+        let mut milestone = Milestone::default();
+        milestone.number = 7;
+        milestone.title = "Sprint 7".to_owned();
+        SprintReport {
+            milestone,
+            points: SprintPoints::new(10, 13, 2).unwrap(),
+            completed: vec![ReportIssue {
+                number: 41,
+                title: "Ship it".to_owned(),
+                points: 8,
+            }],
+            incomplete: vec![ReportIssue {
+                number: 42,
+                title: "Nearly there".to_owned(),
+                points: 2,
+            }],
+        }
+    }
+
+    #[test]
+    fn sprint_points_bounds() {
+        let points = SprintPoints::new(10, 13, 2).unwrap();
+        assert_eq!(points.done_in_sprint, 8);
+        assert_eq!(points.done_out_of_sprint, 3);
+        assert_eq!(points.done_total, 11);
+
+        assert!(SprintPoints::new(1, 13, 2).is_err());
+        assert!(SprintPoints::new(14, 13, 2).is_err());
+    }
+
+    #[test]
+    fn sprint_points_invalid_planned() {
+        match SprintPoints::new(1, 13, 2) {
+            Err(Error::Points { description }) => assert_eq!(
+                description,
+                "Planned points too low: 2 points are still open in the milestone."
+            ),
+            other => panic!("Expected points error, got {:?}", other),
+        }
+        match SprintPoints::new(14, 13, 2) {
+            Err(Error::Points { description }) => assert_eq!(
+                description,
+                "Planned points too high: there are only 13 points in the milestone."
+            ),
+            other => panic!("Expected points error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn sprint_report_display() {
+        assert_eq!(
+            mock_report().to_string(),
+            r#"*Sprint 7* Report
+---
+We completed *8* planned points out of *10* (2 remaining).
+We also did 3 out of sprint points.
+In total, we finished *11 points* of work."#
+        );
+    }
+
+    #[test]
+    fn sprint_report_serialize() {
+        assert_eq!(
+            serde_json::to_value(mock_report()).unwrap(),
+            json!({
+                "milestone": {
+                    "id": 0,
+                    "number": 7,
+                    "title": "Sprint 7",
+                    "state": "open",
+                    "due_on": "1970-01-01T00:00:00+00:00"
+                },
+                "points": {
+                    "planned": 10,
+                    "in_milestone": 13,
+                    "in_milestone_open": 2,
+                    "done_in_sprint": 8,
+                    "done_out_of_sprint": 3,
+                    "done_total": 11
+                },
+                "completed": [{"number": 41, "title": "Ship it", "points": 8}],
+                "incomplete": [{"number": 42, "title": "Nearly there", "points": 2}]
+            })
+        );
+    }
+
+    #[test]
+    fn done_points_by_closed_at() {
+        let at = |day| FixedOffset::east(0).ymd(2020, 5, day).and_hms(12, 0, 0);
+        let closed = |number, day| {
+            let mut issue = Issue::default();
+            issue.number = number;
+            issue.closed_at = Some(at(day));
+            issue
+        };
+        let mut open = Issue::default();
+        open.number = 5;
+        open.closed_at = None;
+        let issues = vec![
+            closed(1, 1),
+            closed(2, 4),
+            closed(3, 14),
+            closed(4, 20),
+            open,
+            closed(6, 10),
+        ];
+        let estimates = vec![(1, 1), (2, 2), (3, 3), (4, 5), (5, 8)]
+            .into_iter()
+            .collect::<HashMap<_, _>>();
+
+        // Issues 2 and 3 were closed in the sprint; 6 has no estimate
+        assert_eq!(
+            compute_done_points_by_closed_at(&issues, &estimates, &at(2), &at(14)),
+            5
+        );
+        assert_eq!(
+            compute_done_points_by_closed_at(&issues, &estimates, &at(21), &at(28)),
+            0
+        );
+    }
+
     #[test]
     fn issue_assigned_to_milestone() {
         let milestone = Milestone::default();
@@ -13,6 +13,9 @@ pub enum Error {
         status: StatusCode,
     },
 
+    #[snafu(display("Ambiguous result: {}", description))]
+    Ambiguous { description: String },
+
     #[snafu(display("Decadog config error: {}", description))]
     Config { description: String },
 
@@ -22,6 +25,12 @@ pub enum Error {
         status: StatusCode,
     },
 
+    #[snafu(display("Not found: {}", description))]
+    NotFound { description: String },
+
+    #[snafu(display("Invalid sprint points: {}", description))]
+    Points { description: String },
+
     #[snafu(display("Reqwest error: {}", source))]
     Reqwest { source: ReqwestError },
 
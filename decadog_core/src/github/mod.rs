@@ -5,21 +5,30 @@ use std::hash::Hasher;
 
 use chrono::{DateTime, FixedOffset, TimeZone};
 use log::debug;
-use reqwest::header::{HeaderMap, AUTHORIZATION};
+use reqwest::header::{HeaderMap, HeaderValue, ACCEPT, AUTHORIZATION};
 use reqwest::{
     blocking::{Client as ReqwestClient, ClientBuilder, RequestBuilder},
     Method, Url,
 };
+use serde::de::DeserializeOwned;
 use serde_derive::{Deserialize, Serialize};
+use serde_json::Value;
 
 use crate::error::Error;
 
 pub mod paginate;
 pub mod request;
 
-use paginate::PaginatedSearch;
+use paginate::{PaginatedList, PaginatedSearch};
 use request::RequestBuilderExt;
 
+/// Media type requested from the Github API by default.
+pub const DEFAULT_ACCEPT: &str = "application/vnd.github.v3+json";
+
+/// Media type required to preview the reactions API.
+pub const REACTIONS_PREVIEW_ACCEPT: &str = "application/vnd.github.squirrel-girl-preview+json";
+
+#[derive(Clone)]
 pub struct Client {
     id: u64,
     reqwest_client: ReqwestClient,
@@ -48,6 +57,22 @@ pub struct GithubClientErrorBody {
     pub documentation_url: Option<String>,
 }
 
+/// Parse the base url of the Github API.
+///
+/// A trailing `/` is added if missing, as otherwise the last segment of the path (such as
+/// `v3` in `https://github.example.com/api/v3`) would be replaced when joining endpoints.
+fn base_url(url: &str) -> Result<Url, Error> {
+    let mut base_url = Url::parse(url).map_err(|_| Error::Config {
+        description: format!("Invalid Github base url {}", url),
+    })?;
+    if !base_url.path().ends_with('/') {
+        debug!("Adding trailing slash to Github base url {}", url);
+        let path = format!("{}/", base_url.path());
+        base_url.set_path(&path);
+    }
+    Ok(base_url)
+}
+
 impl Client {
     /// Create a new client that can make requests to the Github API using token auth.
     pub fn new(url: &str, token: &str) -> Result<Client, Error> {
@@ -62,15 +87,14 @@ impl Client {
                     description: "Invalid Github token for Authorization header.".to_owned(),
                 })?,
         );
+        headers.insert(ACCEPT, HeaderValue::from_static(DEFAULT_ACCEPT));
 
         let reqwest_client = ClientBuilder::new()
             .default_headers(headers)
             .user_agent("decadog")
             .build()?;
 
-        let base_url = Url::parse(url).map_err(|_| Error::Config {
-            description: format!("Invalid Github base url {}", url),
-        })?;
+        let base_url = base_url(url)?;
 
         let mut hasher = DefaultHasher::new();
         hasher.write(url.as_bytes());
@@ -88,17 +112,46 @@ impl Client {
         self.id
     }
 
+    /// Url of an API endpoint, given by its path relative to the base url.
+    ///
+    /// Any leading `/` is ignored, so that the path of the base url is always kept.
+    fn endpoint(&self, path: &str) -> Result<Url, Error> {
+        Ok(self.base_url.join(path.trim_start_matches('/'))?)
+    }
+
     /// Returns a `request::RequestBuilder` authorized to the Github API.
     pub fn request(&self, method: Method, url: Url) -> RequestBuilder {
         debug!("{} {}", method, url.as_str());
         self.reqwest_client.request(method, url)
     }
 
+    /// Returns a `request::RequestBuilder` authorized to the Github API, accepting the given
+    /// media type instead of `DEFAULT_ACCEPT`.
+    ///
+    /// This is required for API previews, such as `REACTIONS_PREVIEW_ACCEPT`.
+    pub fn request_accepting(&self, method: Method, url: Url, accept: &str) -> RequestBuilder {
+        self.request(method, url).header(ACCEPT, accept)
+    }
+
+    /// Get an arbitrary path, relative to the base url, as raw JSON.
+    ///
+    /// This is an escape hatch for endpoints not otherwise modelled by the client.
+    pub fn get_json(&self, path: &str) -> Result<Value, Error> {
+        self.request(Method::GET, self.endpoint(path)?)
+            .send_github()
+    }
+
+    /// Get the current rate limit status.
+    pub fn get_rate_limit(&self) -> Result<RateLimit, Error> {
+        self.request(Method::GET, self.endpoint("rate_limit")?)
+            .send_github()
+    }
+
     /// Get an issue by owner, repo name and issue number.
     pub fn get_issue(&self, owner: &str, repo: &str, issue_number: u32) -> Result<Issue, Error> {
         self.request(
             Method::GET,
-            self.base_url.join(&format!(
+            self.endpoint(&format!(
                 "/repos/{}/{}/issues/{}",
                 owner, repo, issue_number
             ))?,
@@ -110,19 +163,68 @@ impl Client {
     pub fn get_repository(&self, owner: &str, repo: &str) -> Result<Repository, Error> {
         self.request(
             Method::GET,
-            self.base_url.join(&format!("/repos/{}/{}", owner, repo))?,
+            self.endpoint(&format!("/repos/{}/{}", owner, repo))?,
         )
         .send_github()
     }
 
     /// Get members by organisation.
     pub fn get_members(&self, organisation: &str) -> Result<Vec<OrganisationMember>, Error> {
-        self.request(
-            Method::GET,
-            self.base_url
-                .join(&format!("orgs/{}/members", organisation))?,
-        )
-        .send_github()
+        let request = self
+            .request(
+                Method::GET,
+                self.endpoint(&format!("orgs/{}/members", organisation))?,
+            )
+            .query(&[("per_page", "100")])
+            .build()?;
+
+        PaginatedList::<OrganisationMember>::new(&self.reqwest_client, request)?.collect()
+    }
+
+    /// Get the member of an organisation with the given login.
+    ///
+    /// Logins are matched case insensitively, as on Github. Errors if there is no such member.
+    pub fn resolve_member(
+        &self,
+        organisation: &str,
+        login: &str,
+    ) -> Result<OrganisationMember, Error> {
+        self.get_members(organisation)?
+            .into_iter()
+            .find(|member| member.login.eq_ignore_ascii_case(login))
+            .ok_or_else(|| Error::NotFound {
+                description: format!("'{}' is not a member of {}.", login, organisation),
+            })
+    }
+
+    /// Get users who can be assigned to issues in a repository.
+    pub fn get_assignable_users(
+        &self,
+        owner: &str,
+        repo: &str,
+    ) -> Result<Vec<OrganisationMember>, Error> {
+        let request = self
+            .request(
+                Method::GET,
+                self.endpoint(&format!("/repos/{}/{}/assignees", owner, repo))?,
+            )
+            .query(&[("per_page", "100")])
+            .build()?;
+
+        PaginatedList::<OrganisationMember>::new(&self.reqwest_client, request)?.collect()
+    }
+
+    /// Get all repositories by organisation.
+    pub fn get_org_repositories(&self, organisation: &str) -> Result<Vec<Repository>, Error> {
+        let request = self
+            .request(
+                Method::GET,
+                self.endpoint(&format!("orgs/{}/repos", organisation))?,
+            )
+            .query(&[("per_page", "100")])
+            .build()?;
+
+        PaginatedList::<Repository>::new(&self.reqwest_client, request)?.collect()
     }
 
     /// Get milestones by owner and repo name.
@@ -134,13 +236,53 @@ impl Client {
         };
         self.request(
             Method::GET,
-            self.base_url
-                .join(&format!("/repos/{}/{}/milestones", owner, repo))?,
+            self.endpoint(&format!("/repos/{}/{}/milestones", owner, repo))?,
         )
         .query(&query)
         .send_github()
     }
 
+    /// Get milestones in any state by owner and repo name.
+    pub fn get_all_milestones(&self, owner: &str, repo: &str) -> Result<Vec<Milestone>, Error> {
+        let query = GetMilestones {
+            state: Some(SearchState::All),
+            sort: None,
+            direction: None,
+        };
+        let request = self
+            .request(
+                Method::GET,
+                self.endpoint(&format!("/repos/{}/{}/milestones", owner, repo))?,
+            )
+            .query(&query)
+            .query(&[("per_page", "100")])
+            .build()?;
+
+        PaginatedList::<Milestone>::new(&self.reqwest_client, request)?.collect()
+    }
+
+    /// Get a milestone in any state by exact title, or `None` if there is no match.
+    ///
+    /// Errors if several milestones share the title.
+    pub fn get_milestone_by_title(
+        &self,
+        owner: &str,
+        repo: &str,
+        title: &str,
+    ) -> Result<Option<Milestone>, Error> {
+        let mut matches = self
+            .get_all_milestones(owner, repo)?
+            .into_iter()
+            .filter(|milestone| milestone.title == title)
+            .collect::<Vec<_>>();
+        if matches.len() > 1 {
+            return Err(Error::Ambiguous {
+                description: format!("{} milestones are titled '{}'.", matches.len(), title),
+            });
+        }
+        Ok(matches.pop())
+    }
+
     /// Get milestones by owner and repo name.
     pub fn create_milestone(
         &self,
@@ -150,8 +292,7 @@ impl Client {
     ) -> Result<Milestone, Error> {
         self.request(
             Method::POST,
-            self.base_url
-                .join(&format!("/repos/{}/{}/milestones", owner, repo))?,
+            self.endpoint(&format!("/repos/{}/{}/milestones", owner, repo))?,
         )
         .json(&create)
         .send_github()
@@ -167,7 +308,7 @@ impl Client {
     ) -> Result<Issue, Error> {
         self.request(
             Method::PATCH,
-            self.base_url.join(&format!(
+            self.endpoint(&format!(
                 "/repos/{}/{}/issues/{}",
                 owner, repo, issue_number
             ))?,
@@ -176,14 +317,61 @@ impl Client {
         .send_github()
     }
 
+    /// Replace all labels on an issue with the given label names.
+    ///
+    /// Returns the resulting labels on the issue.
+    pub fn set_issue_labels(
+        &self,
+        owner: &str,
+        repo: &str,
+        issue_number: u32,
+        labels: &[String],
+    ) -> Result<Vec<Label>, Error> {
+        self.request(
+            Method::PUT,
+            self.endpoint(&format!(
+                "/repos/{}/{}/issues/{}/labels",
+                owner, repo, issue_number
+            ))?,
+        )
+        .json(&LabelsUpdate { labels })
+        .send_github()
+    }
+
+    /// Get reactions to an issue.
+    pub fn get_issue_reactions(
+        &self,
+        owner: &str,
+        repo: &str,
+        issue_number: u32,
+    ) -> Result<Vec<Reaction>, Error> {
+        self.request_accepting(
+            Method::GET,
+            self.endpoint(&format!(
+                "/repos/{}/{}/issues/{}/reactions",
+                owner, repo, issue_number
+            ))?,
+            REACTIONS_PREVIEW_ACCEPT,
+        )
+        .send_github()
+    }
+
     /// Search issues.
     pub fn search_issues(&self, query: &SearchIssues) -> Result<PaginatedSearch<Issue>, Error> {
+        self.search(query)
+    }
+
+    /// Search issues, deserializing results into any shape `T`, such as `IssueSummary`.
+    pub fn search<T>(&self, query: &SearchIssues) -> Result<PaginatedSearch<T>, Error>
+    where
+        T: DeserializeOwned,
+    {
         let builder = self
-            .request(Method::GET, self.base_url.join("search/issues")?)
+            .request(Method::GET, self.endpoint("search/issues")?)
             .query(&query);
         let request = builder.build()?;
 
-        PaginatedSearch::<Issue>::new(&self.reqwest_client, request)
+        PaginatedSearch::<T>::new(&self.reqwest_client, request)
     }
 
     pub fn patch_milestone(
@@ -195,7 +383,7 @@ impl Client {
     ) -> Result<Milestone, Error> {
         self.request(
             Method::PATCH,
-            self.base_url.join(&format!(
+            self.endpoint(&format!(
                 "/repos/{}/{}/milestones/{}",
                 owner, repo, milestone_number
             ))?,
@@ -205,6 +393,17 @@ impl Client {
     }
 }
 
+/// Drop pull requests from search results, leaving only issues.
+pub fn exclude_pull_requests<I>(issues: I) -> impl Iterator<Item = Result<Issue, Error>>
+where
+    I: IntoIterator<Item = Result<Issue, Error>>,
+{
+    issues.into_iter().filter(|issue| match issue {
+        Ok(issue) => !issue.is_pull_request(),
+        Err(_) => true,
+    })
+}
+
 /// Update an issue.
 #[derive(Deserialize, Serialize, Debug, Clone, Default)]
 pub struct IssueUpdate {
@@ -216,6 +415,17 @@ pub struct IssueUpdate {
     pub assignees: Option<Vec<String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub state: Option<State>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state_reason: Option<StateReason>,
+}
+
+/// Reason for the state of an issue.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum StateReason {
+    Completed,
+    NotPlanned,
+    Reopened,
 }
 
 /// A search filter for state.
@@ -274,6 +484,21 @@ impl SearchQueryBuilder {
         self.key_value("label", label_name)
     }
 
+    /// Issues with any of the given labels.
+    ///
+    /// If no labels are given, no filter is applied, as an empty `label:` is invalid.
+    pub fn any_label<S: AsRef<str>>(&mut self, label_names: &[S]) -> &mut Self {
+        if label_names.is_empty() {
+            return self;
+        }
+        let label_names = label_names
+            .iter()
+            .map(|label_name| format!(r#""{}""#, label_name.as_ref()))
+            .collect::<Vec<_>>()
+            .join(",");
+        self.key_value("label", &label_names)
+    }
+
     pub fn not_label(&mut self, label_name: &str) -> &mut Self {
         self.key_value("-label", label_name)
     }
@@ -282,6 +507,14 @@ impl SearchQueryBuilder {
         self.key_value("type", "issue")
     }
 
+    /// Issues of the given issue type, such as Bug or Feature.
+    pub fn issue_type(&mut self, issue_type_name: &str) -> &mut Self {
+        self.term(&format!(
+            r#"type:"{}""#,
+            issue_type_name.replace('"', r#"\""#)
+        ))
+    }
+
     pub fn state(&mut self, state: &State) -> &mut Self {
         self.key_value(
             "state",
@@ -290,7 +523,14 @@ impl SearchQueryBuilder {
     }
 
     pub fn milestone(&mut self, milestone_title: &str) -> &mut Self {
-        self.term(&format!(r#"milestone:"{}""#, milestone_title))
+        self.term(&format!(
+            r#"milestone:"{}""#,
+            milestone_title.replace('"', r#"\""#)
+        ))
+    }
+
+    pub fn milestone_of(&mut self, milestone: &Milestone) -> &mut Self {
+        self.milestone(&milestone.title)
     }
 
     pub fn no_milestone(&mut self) -> &mut Self {
@@ -305,6 +545,22 @@ impl SearchQueryBuilder {
             .term(&format!("closed:>={}", &datetime.format("%Y-%m-%d")))
     }
 
+    /// Issues closed between `start` and `end`, inclusive.
+    pub fn closed_between<Tz: TimeZone>(
+        &mut self,
+        start: &DateTime<Tz>,
+        end: &DateTime<Tz>,
+    ) -> &mut Self
+    where
+        Tz::Offset: fmt::Display,
+    {
+        self.state(&State::Closed).term(&format!(
+            "closed:{}..{}",
+            &start.format("%Y-%m-%d"),
+            &end.format("%Y-%m-%d")
+        ))
+    }
+
     pub fn owner_repo(&mut self, owner: &str, repo: &str) -> &mut Self {
         self.term(&format!("repo:{}/{}", owner, repo))
     }
@@ -379,6 +635,20 @@ pub struct Label {
     pub name: String,
 }
 
+/// A reaction to an issue or comment, such as `+1` or `heart`.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct Reaction {
+    pub id: u32,
+    pub user: OrganisationMember,
+    pub content: String,
+}
+
+/// Request body to set the labels of an issue.
+#[derive(Serialize, Debug)]
+struct LabelsUpdate<'a> {
+    labels: &'a [String],
+}
+
 /// A Github status.
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "lowercase")]
@@ -401,19 +671,143 @@ pub struct Issue {
     pub state: State,
     pub title: String,
     pub milestone: Option<Milestone>,
+    #[serde(default)]
     pub assignees: Vec<OrganisationMember>,
+    #[serde(default)]
     pub labels: Vec<Label>,
     pub created_at: DateTime<FixedOffset>,
     pub updated_at: DateTime<FixedOffset>,
     pub closed_at: Option<DateTime<FixedOffset>>,
     pub html_url: String,
+    /// Issue type, if the organisation uses them.
+    #[serde(default, rename = "type")]
+    pub issue_type: Option<IssueType>,
+    /// API url of the repository this issue belongs to.
+    #[serde(default)]
+    pub repository_url: Option<String>,
+    /// Present if this issue is a pull request.
+    pub pull_request: Option<PullRequestRef>,
+}
+
+/// A Github issue type, such as Bug or Feature.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct IssueType {
+    pub id: u64,
+    pub name: String,
+}
+
+/// A lightweight view of a Github Issue, for when the full issue is not required.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(from = "IssueSummaryFields")]
+pub struct IssueSummary {
+    pub number: u32,
+    pub state: State,
+    pub title: String,
+    pub milestone_id: Option<u32>,
+    pub assignee_logins: Vec<String>,
+}
+
+/// Reference to an object by id.
+#[derive(Deserialize, Debug)]
+struct IdRef {
+    id: u32,
+}
+
+/// Reference to a user by login.
+#[derive(Deserialize, Debug)]
+struct LoginRef {
+    login: String,
+}
+
+/// The subset of Github Issue fields used by `IssueSummary`.
+#[derive(Deserialize, Debug)]
+struct IssueSummaryFields {
+    number: u32,
+    state: State,
+    title: String,
+    milestone: Option<IdRef>,
+    #[serde(default)]
+    assignees: Vec<LoginRef>,
+}
+
+impl From<IssueSummaryFields> for IssueSummary {
+    fn from(fields: IssueSummaryFields) -> Self {
+        Self {
+            number: fields.number,
+            state: fields.state,
+            title: fields.title,
+            milestone_id: fields.milestone.map(|milestone| milestone.id),
+            assignee_logins: fields
+                .assignees
+                .into_iter()
+                .map(|assignee| assignee.login)
+                .collect(),
+        }
+    }
+}
+
+/// A reference from an issue to its pull request.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct PullRequestRef {
+    pub url: String,
+    pub html_url: String,
+}
+
+impl Issue {
+    /// Whether this issue is actually a pull request.
+    pub fn is_pull_request(&self) -> bool {
+        self.pull_request.is_some()
+    }
+
+    /// Owner and name of the repository this issue belongs to, from `repository_url`.
+    ///
+    /// Useful to tell apart issues from searches across several repositories.
+    pub fn repository(&self) -> Option<(&str, &str)> {
+        let url = self.repository_url.as_ref()?;
+        let path = &url[url.find("/repos/")? + "/repos/".len()..];
+        let mut parts = path.trim_end_matches('/').split('/');
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some(owner), Some(repo), None) if !owner.is_empty() && !repo.is_empty() => {
+                Some((owner, repo))
+            }
+            _ => None,
+        }
+    }
+
+    /// Title truncated to at most `max` characters, ending in an ellipsis if shortened.
+    pub fn truncated_title(&self, max: usize) -> String {
+        if self.title.chars().count() <= max {
+            return self.title.clone();
+        }
+        let mut truncated: String = self.title.chars().take(max.saturating_sub(1)).collect();
+        if max > 0 {
+            truncated.push('…');
+        }
+        truncated
+    }
+}
+
+/// Github API rate limit status.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct RateLimit {
+    pub rate: RateLimitStatus,
+}
+
+/// Rate limit status for a single resource.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct RateLimitStatus {
+    pub limit: u32,
+    pub remaining: u32,
+    /// UTC epoch seconds at which the limit resets.
+    pub reset: u64,
 }
 
 /// A Github Repository.
-#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
 pub struct Repository {
     pub id: u64,
     pub name: String,
+    pub full_name: String,
 }
 
 impl fmt::Display for Milestone {
@@ -434,7 +828,9 @@ pub mod tests {
     use lazy_static::lazy_static;
     use mockito::mock;
     use pretty_assertions::assert_eq;
+    use serde_json::json;
 
+    use super::paginate::GithubSearchResults;
     use super::*;
 
     const MOCK_GITHUB_TOKEN: &str = "mock_token";
@@ -456,6 +852,32 @@ pub mod tests {
         }
     }
 
+    #[test]
+    fn endpoint_base_url_trailing_slash() {
+        for (base_url, expected) in &[
+            ("https://api.github.com", "https://api.github.com/"),
+            (
+                "https://github.example.com/api/v3",
+                "https://github.example.com/api/v3/",
+            ),
+        ] {
+            let without_slash = Client::new(base_url, MOCK_GITHUB_TOKEN).unwrap();
+            let with_slash = Client::new(&format!("{}/", base_url), MOCK_GITHUB_TOKEN).unwrap();
+            for client in &[without_slash, with_slash] {
+                for path in &["/repos/tommilligan/decadog", "repos/tommilligan/decadog"] {
+                    assert_eq!(
+                        client.endpoint(path).unwrap().as_str(),
+                        format!("{}repos/tommilligan/decadog", expected)
+                    );
+                }
+                assert_eq!(
+                    client.endpoint("rate_limit").unwrap().as_str(),
+                    format!("{}rate_limit", expected)
+                );
+            }
+        }
+    }
+
     #[test]
     fn search_query_builder() {
         assert_eq!(SearchQueryBuilder::new().build(), "");
@@ -467,6 +889,12 @@ pub mod tests {
             SearchQueryBuilder::new().issue().label("spam").build(),
             "type:issue label:spam"
         );
+        assert_eq!(
+            SearchQueryBuilder::new()
+                .any_label(&["team-a", "good first issue"])
+                .build(),
+            r#"label:"team-a","good first issue""#
+        );
         assert_eq!(
             SearchQueryBuilder::new()
                 .milestone("Sprint 2")
@@ -491,6 +919,396 @@ pub mod tests {
                 .build(),
             "state:closed closed:>=2011-04-22 repo:ow/re"
         );
+        assert_eq!(
+            SearchQueryBuilder::new()
+                .closed_between(
+                    &FixedOffset::east(0)
+                        .from_utc_datetime(&NaiveDate::from_ymd(2011, 4, 22).and_hms(13, 33, 48)),
+                    &FixedOffset::east(0)
+                        .from_utc_datetime(&NaiveDate::from_ymd(2011, 5, 5).and_hms(12, 0, 0)),
+                )
+                .build(),
+            "state:closed closed:2011-04-22..2011-05-05"
+        );
+    }
+
+    #[test]
+    fn search_query_builder_issue_type() {
+        assert_eq!(
+            SearchQueryBuilder::new()
+                .issue()
+                .issue_type("Bug")
+                .issue_type("Tech Debt")
+                .build(),
+            r#"type:issue type:"Bug" type:"Tech Debt""#
+        );
+    }
+
+    #[test]
+    fn deserialize_issue_type() {
+        let issue_json = json!({
+            "id": 1,
+            "number": 7,
+            "state": "open",
+            "title": "Typed",
+            "milestone": null,
+            "created_at": "2011-04-22T13:33:48Z",
+            "updated_at": "2011-04-22T13:33:48Z",
+            "html_url": "http://foo.bar",
+            "type": {
+                "id": 2_981_234,
+                "node_id": "IT_kwDOAAAAAM4ALX7y",
+                "name": "Bug",
+                "description": "An unexpected problem or behavior",
+                "color": "red"
+            }
+        });
+        let issue: Issue = serde_json::from_value(issue_json.clone()).unwrap();
+        assert_eq!(
+            issue.issue_type,
+            Some(IssueType {
+                id: 2_981_234,
+                name: "Bug".to_owned()
+            })
+        );
+
+        let mut untyped = issue_json;
+        untyped.as_object_mut().unwrap().remove("type");
+        let issue: Issue = serde_json::from_value(untyped).unwrap();
+        assert_eq!(issue.issue_type, None);
+    }
+
+    #[test]
+    fn search_query_builder_milestone_escaped() {
+        let mut milestone = Milestone::default();
+        milestone.title = r#"Sprint "Alpha" 3"#.to_owned();
+        assert_eq!(
+            SearchQueryBuilder::new().milestone_of(&milestone).build(),
+            r#"milestone:"Sprint \"Alpha\" 3""#
+        );
+    }
+
+    #[test]
+    fn issue_truncated_title() {
+        let mut issue = Issue::default();
+        issue.title = "Fix the flux capacitor".to_owned();
+        assert_eq!(issue.truncated_title(10), "Fix the f…");
+        assert_eq!(issue.truncated_title(22), "Fix the flux capacitor");
+        assert_eq!(issue.truncated_title(0), "");
+
+        issue.title = "Réparer le condensateur 🚗💨🔥".to_owned();
+        assert_eq!(issue.truncated_title(5), "Répa…");
+        assert_eq!(issue.truncated_title(26), "Réparer le condensateur 🚗…");
+
+        issue.title = "Short".to_owned();
+        assert_eq!(issue.truncated_title(80), "Short");
+    }
+
+    #[test]
+    fn test_search_excludes_pull_requests() {
+        let body = r#"{
+  "incomplete_results": false,
+  "items": [
+    {
+      "id": 1,
+      "number": 31,
+      "state": "closed",
+      "title": "An issue",
+      "assignees": [],
+      "milestone": null,
+      "labels": [],
+      "created_at": "2011-04-22T13:33:48Z",
+      "updated_at": "2011-04-22T13:33:48Z",
+      "closed_at": "2011-04-22T13:33:48Z",
+      "html_url": "http://foo.bar/issues/31"
+    },
+    {
+      "id": 2,
+      "number": 32,
+      "state": "closed",
+      "title": "A pull request",
+      "assignees": [],
+      "milestone": null,
+      "labels": [],
+      "created_at": "2011-04-22T13:33:48Z",
+      "updated_at": "2011-04-22T13:33:48Z",
+      "closed_at": "2011-04-22T13:33:48Z",
+      "html_url": "http://foo.bar/pull/32",
+      "pull_request": {
+        "url": "http://api.foo.bar/pulls/32",
+        "html_url": "http://foo.bar/pull/32",
+        "diff_url": "http://foo.bar/pull/32.diff",
+        "patch_url": "http://foo.bar/pull/32.patch"
+      }
+    }
+  ]
+}"#;
+        let mock = mock("GET", "/search/issues?q=pull+requests+mixed+in")
+            .match_header("authorization", "token mock_token")
+            .with_status(200)
+            .with_body(body)
+            .create();
+
+        let results = MOCK_GITHUB_CLIENT
+            .search_issues(&SearchIssues {
+                q: "pull requests mixed in",
+                ..SearchIssues::default()
+            })
+            .unwrap()
+            .collect::<Vec<_>>();
+        mock.assert();
+
+        assert_eq!(
+            results
+                .iter()
+                .map(|issue| issue.as_ref().unwrap().is_pull_request())
+                .collect::<Vec<_>>(),
+            vec![false, true]
+        );
+        let issues = exclude_pull_requests(results)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(
+            issues.iter().map(|issue| issue.number).collect::<Vec<_>>(),
+            vec![31]
+        );
+    }
+
+    #[test]
+    fn test_get_org_repositories() {
+        let page_one = mock("GET", "/orgs/octo-org/repos?per_page=100")
+            .match_header("authorization", "token mock_token")
+            .with_status(200)
+            .with_header(
+                "link",
+                &format!(
+                    r#"<{}/orgs/octo-org/repos?per_page=100&page=2>; rel="next""#,
+                    &mockito::server_url()
+                ),
+            )
+            .with_body(
+                r#"[
+  {
+    "id": 1296269,
+    "name": "Hello-World",
+    "full_name": "octo-org/Hello-World",
+    "private": false
+  }
+]"#,
+            )
+            .create();
+        let page_two = mock("GET", "/orgs/octo-org/repos?per_page=100&page=2")
+            .match_header("authorization", "token mock_token")
+            .with_status(200)
+            .with_body(
+                r#"[
+  {
+    "id": 1296270,
+    "name": "Spoon-Knife",
+    "full_name": "octo-org/Spoon-Knife",
+    "private": true
+  }
+]"#,
+            )
+            .create();
+
+        let repositories = MOCK_GITHUB_CLIENT.get_org_repositories("octo-org").unwrap();
+        page_one.assert();
+        page_two.assert();
+
+        assert_eq!(
+            repositories,
+            vec![
+                Repository {
+                    id: 1_296_269,
+                    name: "Hello-World".to_owned(),
+                    full_name: "octo-org/Hello-World".to_owned(),
+                },
+                Repository {
+                    id: 1_296_270,
+                    name: "Spoon-Knife".to_owned(),
+                    full_name: "octo-org/Spoon-Knife".to_owned(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_get_assignable_users() {
+        let mock = mock("GET", "/repos/tommilligan/decadog/assignees?per_page=100")
+            .match_header("authorization", "token mock_token")
+            .with_status(200)
+            .with_body(
+                r#"[
+  {
+    "login": "octocat",
+    "id": 1,
+    "type": "User",
+    "site_admin": false
+  }
+]"#,
+            )
+            .create();
+
+        let users = MOCK_GITHUB_CLIENT
+            .get_assignable_users("tommilligan", "decadog")
+            .unwrap();
+        mock.assert();
+
+        assert_eq!(
+            users,
+            vec![OrganisationMember {
+                login: "octocat".to_owned(),
+                id: 1,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_search_issue_summaries() {
+        let body = r#"{
+  "incomplete_results": false,
+  "items": [
+    {
+      "id": 1,
+      "number": 51,
+      "state": "open",
+      "title": "Summarise me",
+      "body": "A very long description that we never need...",
+      "assignees": [
+        {
+          "login": "tommilligan",
+          "id": 1
+        }
+      ],
+      "milestone": {
+        "id": 1002604,
+        "number": 1,
+        "state": "open",
+        "title": "v1.0",
+        "due_on": "2012-10-09T23:39:01Z"
+      },
+      "labels": [],
+      "created_at": "2011-04-22T13:33:48Z",
+      "updated_at": "2011-04-22T13:33:48Z",
+      "html_url": "http://foo.bar/issues/51"
+    },
+    {
+      "number": 52,
+      "state": "closed",
+      "title": "Minimal",
+      "milestone": null
+    }
+  ]
+}"#;
+        let mock = mock("GET", "/search/issues?q=summaries")
+            .match_header("authorization", "token mock_token")
+            .with_status(200)
+            .with_body(body)
+            .create();
+
+        let summaries = MOCK_GITHUB_CLIENT
+            .search::<IssueSummary>(&SearchIssues {
+                q: "summaries",
+                ..SearchIssues::default()
+            })
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        mock.assert();
+
+        assert_eq!(
+            summaries,
+            vec![
+                IssueSummary {
+                    number: 51,
+                    state: State::Open,
+                    title: "Summarise me".to_owned(),
+                    milestone_id: Some(1_002_604),
+                    assignee_logins: vec!["tommilligan".to_owned()],
+                },
+                IssueSummary {
+                    number: 52,
+                    state: State::Closed,
+                    title: "Minimal".to_owned(),
+                    milestone_id: None,
+                    assignee_logins: vec![],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_issue_repository() {
+        let body = r#"{
+  "incomplete_results": false,
+  "items": [
+    {
+      "id": 1,
+      "number": 51,
+      "state": "open",
+      "title": "In decadog",
+      "milestone": null,
+      "created_at": "2011-04-22T13:33:48Z",
+      "updated_at": "2011-04-22T13:33:48Z",
+      "html_url": "https://github.com/tommilligan/decadog/issues/51",
+      "repository_url": "https://api.github.com/repos/tommilligan/decadog"
+    },
+    {
+      "id": 2,
+      "number": 3,
+      "state": "open",
+      "title": "In scout",
+      "milestone": null,
+      "created_at": "2011-04-22T13:33:48Z",
+      "updated_at": "2011-04-22T13:33:48Z",
+      "html_url": "https://github.com/jhbabon/scout/issues/3",
+      "repository_url": "https://github.example.com/api/v3/repos/jhbabon/scout"
+    },
+    {
+      "id": 3,
+      "number": 4,
+      "state": "open",
+      "title": "Unknown",
+      "milestone": null,
+      "created_at": "2011-04-22T13:33:48Z",
+      "updated_at": "2011-04-22T13:33:48Z",
+      "html_url": "https://github.com/jhbabon/scout/issues/4"
+    }
+  ]
+}"#;
+        let results: GithubSearchResults<Issue> = serde_json::from_str(body).unwrap();
+        let repositories = results
+            .items
+            .iter()
+            .map(Issue::repository)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            repositories,
+            vec![
+                Some(("tommilligan", "decadog")),
+                Some(("jhbabon", "scout")),
+                None
+            ]
+        );
+    }
+
+    #[test]
+    fn test_get_json() {
+        let mock = mock("GET", "/rate_limit")
+            .match_header("authorization", "token mock_token")
+            .with_status(200)
+            .with_body(r#"{"resources": {"core": {"limit": 5000, "remaining": 4999}}}"#)
+            .create();
+
+        let value = MOCK_GITHUB_CLIENT.get_json("rate_limit").unwrap();
+        mock.assert();
+
+        assert_eq!(
+            value,
+            json!({"resources": {"core": {"limit": 5000, "remaining": 4999}}})
+        );
     }
 
     #[test]
@@ -526,6 +1344,7 @@ pub mod tests {
 }"#;
         let mock = mock("GET", "/repos/tommilligan/decadog/issues/1")
             .match_header("authorization", "token mock_token")
+            .match_header("accept", DEFAULT_ACCEPT)
             .with_status(200)
             .with_body(body)
             .create();
@@ -564,10 +1383,51 @@ pub mod tests {
                     .from_utc_datetime(&NaiveDate::from_ymd(2011, 4, 22).and_hms(13, 33, 48)),
                 closed_at: None,
                 html_url: "http://foo.bar".to_owned(),
+                issue_type: None,
+                repository_url: None,
+                pull_request: None,
             }
         );
     }
 
+    #[test]
+    fn test_get_issue_reactions() {
+        let body = r#"[
+  {
+    "id": 1,
+    "user": {
+      "login": "octocat",
+      "id": 2
+    },
+    "content": "heart",
+    "created_at": "2016-05-20T20:09:31Z"
+  }
+]"#;
+        let mock = mock("GET", "/repos/tommilligan/decadog/issues/1/reactions")
+            .match_header("authorization", "token mock_token")
+            .match_header("accept", REACTIONS_PREVIEW_ACCEPT)
+            .with_status(200)
+            .with_body(body)
+            .create();
+
+        let reactions = MOCK_GITHUB_CLIENT
+            .get_issue_reactions("tommilligan", "decadog", 1)
+            .unwrap();
+        mock.assert();
+
+        assert_eq!(
+            reactions,
+            vec![Reaction {
+                id: 1,
+                user: OrganisationMember {
+                    login: "octocat".to_owned(),
+                    id: 2
+                },
+                content: "heart".to_owned(),
+            }]
+        );
+    }
+
     #[test]
     fn test_close_issue() {
         let body = r#"{
@@ -613,10 +1473,62 @@ pub mod tests {
                     .from_utc_datetime(&NaiveDate::from_ymd(2011, 4, 22).and_hms(13, 33, 48)),
                 closed_at: None,
                 html_url: "http://foo.bar".to_owned(),
+                issue_type: None,
+                repository_url: None,
+                pull_request: None,
             }
         );
     }
 
+    #[test]
+    fn test_set_issue_labels() {
+        let body = r#"[
+  {
+    "id": 208045946,
+    "node_id": "MDU6TGFiZWwyMDgwNDU5NDY=",
+    "url": "https://api.github.com/repos/tommilligan/decadog/labels/bug",
+    "name": "bug",
+    "description": "Something isn't working",
+    "color": "f29513",
+    "default": true
+  },
+  {
+    "id": 208045947,
+    "name": "status: in review"
+  }
+]"#;
+        let mock = mock("PUT", "/repos/tommilligan/decadog/issues/3/labels")
+            .match_header("authorization", "token mock_token")
+            .match_body(r#"{"labels":["bug","status: in review"]}"#)
+            .with_status(200)
+            .with_body(body)
+            .create();
+
+        let labels = MOCK_GITHUB_CLIENT
+            .set_issue_labels(
+                "tommilligan",
+                "decadog",
+                3,
+                &["bug".to_owned(), "status: in review".to_owned()],
+            )
+            .unwrap();
+        mock.assert();
+
+        assert_eq!(
+            labels,
+            vec![
+                Label {
+                    id: 208_045_946,
+                    name: "bug".to_owned(),
+                },
+                Label {
+                    id: 208_045_947,
+                    name: "status: in review".to_owned(),
+                },
+            ]
+        );
+    }
+
     #[test]
     fn test_close_milestone() {
         let body = r#"{
@@ -1,6 +1,7 @@
+use std::marker::PhantomData;
 use std::vec::IntoIter;
 
-use log::debug;
+use log::{debug, warn};
 use reqwest::blocking::{Client as ReqwestClient, Request, Response};
 use serde::de::DeserializeOwned;
 use serde_derive::{Deserialize, Serialize};
@@ -10,33 +11,84 @@ use crate::error::Error;
 
 use super::request::ResponseExt;
 
+/// Maximum number of results the Github search API returns for any query.
+pub const SEARCH_RESULT_CAP: u64 = 1000;
+
 /// A single page from the Github search API.
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct GithubSearchResults<T> {
+    /// Total number of matches, which may exceed the results available.
+    #[serde(default)]
+    pub total_count: Option<u64>,
     pub incomplete_results: bool,
     pub items: Vec<T>,
 }
 
+/// A single page of items `T` from a paginated Github API.
+pub trait Page<T>: DeserializeOwned {
+    /// Total number of items across all pages, if known.
+    fn total_count(&self) -> Option<u64> {
+        None
+    }
+
+    fn into_items(self) -> Vec<T>;
+}
+
+impl<T> Page<T> for GithubSearchResults<T>
+where
+    T: DeserializeOwned,
+{
+    fn total_count(&self) -> Option<u64> {
+        self.total_count
+    }
+
+    fn into_items(self) -> Vec<T> {
+        self.items
+    }
+}
+
+impl<T> Page<T> for Vec<T>
+where
+    T: DeserializeOwned,
+{
+    fn into_items(self) -> Vec<T> {
+        self
+    }
+}
+
 /// Represents a paginated search query over some collection of items `T`.
 ///
 /// Used as an iterator, the `PaginatedSearch` will continue to fetch more results
 /// until no more are available.
-pub struct PaginatedSearch<'a, T>
+pub type PaginatedSearch<'a, T> = Paginated<'a, T, GithubSearchResults<T>>;
+
+/// Represents a paginated list endpoint, returning a plain array of items `T` per page.
+pub type PaginatedList<'a, T> = Paginated<'a, T, Vec<T>>;
+
+/// Represents a paginated query over some collection of items `T`, returned in pages `P`.
+///
+/// Used as an iterator, `Paginated` will continue to fetch more results
+/// until no more are available.
+pub struct Paginated<'a, T, P>
 where
     Self: Sized,
     T: DeserializeOwned,
+    P: Page<T>,
 {
     client: &'a ReqwestClient,
     page: IntoIter<T>,
     next_page_url: Option<Url>,
+    total_count: Option<u64>,
+    page_type: PhantomData<P>,
 }
 
-impl<'a, T> PaginatedSearch<'a, T>
+impl<'a, T, P> Paginated<'a, T, P>
 where
     Self: Sized,
     T: DeserializeOwned,
+    P: Page<T>,
 {
-    /// Create a new paginated search, and load the first page.
+    /// Create a new paginated query, and load the first page.
     pub fn new(client: &'a ReqwestClient, initial_request: Request) -> Result<Self, Error> {
         // The initial request is a special case
         debug!("{} {}", initial_request.method(), initial_request.url());
@@ -47,6 +99,8 @@ where
             client,
             page: vec![].into_iter(),
             next_page_url: None,
+            total_count: None,
+            page_type: PhantomData,
         };
         new_self.apply_response(response)?;
 
@@ -54,18 +108,34 @@ where
         Ok(new_self)
     }
 
-    /// Apply a response from the search API to update our state:
+    /// Apply a response from the API to update our state:
     /// - store the new items to iterate throught
     /// - extract and store the url for the next page
     fn apply_response(&mut self, response: Response) -> Result<(), Error> {
         self.next_page_url = response.next_page_url()?;
-        self.page = response
-            .into_github::<GithubSearchResults<T>>()?
-            .items
-            .into_iter();
+        let page = response.into_github::<P>()?;
+        if self.total_count.is_none() {
+            self.total_count = page.total_count();
+            if self.hit_result_cap() {
+                warn!(
+                    "Search matched {} results, but only the first {} are available.",
+                    self.total_count.unwrap_or_default(),
+                    SEARCH_RESULT_CAP
+                );
+            }
+        }
+        self.page = page.into_items().into_iter();
         Ok(())
     }
 
+    /// Whether there are more matching results than the Github search API will return.
+    ///
+    /// If so, results are truncated and the query should be narrowed.
+    pub fn hit_result_cap(&self) -> bool {
+        self.total_count
+            .map_or(false, |total_count| total_count > SEARCH_RESULT_CAP)
+    }
+
     /// Fetch the next page, and apply the response to our state.
     fn update_page(&mut self, url: Url) -> Result<(), Error> {
         debug!("GET {}", &url);
@@ -76,10 +146,11 @@ where
     }
 }
 
-impl<'a, T> Iterator for PaginatedSearch<'a, T>
+impl<'a, T, P> Iterator for Paginated<'a, T, P>
 where
     Self: Sized,
     T: DeserializeOwned,
+    P: Page<T>,
 {
     type Item = Result<T, Error>;
 
@@ -182,5 +253,38 @@ pub mod tests {
 
         // As the last page didn't have a link, the next issue should be None
         assert!(paginated_items.next().is_none());
+        assert!(!paginated_items.hit_result_cap());
+    }
+
+    #[test]
+    fn test_paginated_search_result_cap() {
+        let page_path = "/url-for-capped-search";
+        let client = ReqwestClient::new();
+        let initial_request = client
+            .get(&format!("{}{}", &mockito::server_url(), &page_path))
+            .build()
+            .unwrap();
+        let mock_page = mock("GET", page_path)
+            .with_body(
+                r#"{
+  "total_count": 1500,
+  "incomplete_results": false,
+  "items": [
+    {
+      "data": 0
+    }
+  ]
+}"#,
+            )
+            .create();
+
+        let paginated_items = PaginatedSearch::<TestItem>::new(&client, initial_request).unwrap();
+        mock_page.assert();
+
+        assert!(paginated_items.hit_result_cap());
+        assert_eq!(
+            paginated_items.collect::<Result<Vec<_>, _>>().unwrap(),
+            vec![TestItem { data: 0 }]
+        );
     }
 }
@@ -1,15 +1,58 @@
-use lazy_static::lazy_static;
-use regex::Regex;
 use reqwest::blocking::{RequestBuilder, Response};
 use reqwest::header::LINK;
+use reqwest::StatusCode;
 use serde::de::DeserializeOwned;
 use url::Url;
 
 use crate::error::Error;
+use crate::github::GithubClientErrorBody;
 
-lazy_static! {
-    static ref RX_LINK_NEXT: Regex =
-        Regex::new(r#"<(?P<url>[^>]+)>;[^,]* rel="next""#).expect("Invalid link regex.");
+/// Whether a single Link header parameter is a relation including `rel`.
+fn is_relation(param: &str, rel: &str) -> bool {
+    let mut key_value = param.splitn(2, '=');
+    let key = key_value.next().unwrap_or_default().trim();
+    let value = key_value
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .trim_matches('"');
+    key.eq_ignore_ascii_case("rel") && value.split_whitespace().any(|value| value == rel)
+}
+
+/// Find the url with relation `rel` in a Link header value.
+///
+/// Links may be given in any order, with parameters in any order. Urls are delimited
+/// by angle brackets, so may contain commas and semicolons.
+pub fn link_url<'h>(header: &'h str, rel: &str) -> Option<&'h str> {
+    let mut rest = header;
+    while let Some(url_start) = rest.find('<') {
+        let after_url_start = &rest[url_start + 1..];
+        let url_end = after_url_start.find('>')?;
+        let url = &after_url_start[..url_end];
+
+        // Parameters run until the start of the next link
+        let after_url = &after_url_start[url_end + 1..];
+        let params_end = after_url.find('<').unwrap_or(after_url.len());
+        let params = after_url[..params_end].trim().trim_end_matches(',');
+        if params.split(';').any(|param| is_relation(param, rel)) {
+            return Some(url);
+        }
+        rest = &after_url[params_end..];
+    }
+    None
+}
+
+/// Interpret a Github client error body.
+///
+/// Bodies not in the standard Github error format are kept as raw text.
+fn client_error(status: StatusCode, body: String) -> Error {
+    match serde_json::from_str::<GithubClientErrorBody>(&body) {
+        Ok(error) => Error::Github { error, status },
+        Err(_) => Error::Api {
+            description: body,
+            status,
+        },
+    }
 }
 
 /// Interpret a response with potential JSON errors from the Github API.
@@ -32,10 +75,7 @@ impl ResponseExt for Response {
         if status.is_success() {
             Ok(self.json()?)
         } else if status.is_client_error() {
-            Err(Error::Github {
-                error: self.json()?,
-                status,
-            })
+            Err(client_error(status, self.text()?))
         } else {
             Err(Error::Api {
                 description: "Unexpected response status code.".to_owned(),
@@ -45,15 +85,15 @@ impl ResponseExt for Response {
     }
 
     fn next_page_url(&self) -> Result<Option<Url>, Error> {
-        // TODO(tommilligan) Find an actual Link header parsing implementation
         match self.headers().get(LINK) {
             None => Ok(None),
             Some(header_value) => {
-                match RX_LINK_NEXT.captures(header_value.to_str().map_err(|_| Error::Unknown {
+                let header_value = header_value.to_str().map_err(|_| Error::Unknown {
                     description: "Expected Github Link header to be valid.".to_owned(),
-                })?) {
+                })?;
+                match link_url(header_value, "next") {
                     None => Ok(None),
-                    Some(captured_groups) => Ok(Some(Url::parse(&captured_groups["url"])?)),
+                    Some(url) => Ok(Some(Url::parse(url)?)),
                 }
             }
         }
@@ -78,3 +118,96 @@ impl RequestBuilderExt for RequestBuilder {
         response.into_github()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn client_error_github_body() {
+        let body = r#"{"message": "Validation Failed", "documentation_url": "https://developer.github.com/v3"}"#;
+        match client_error(StatusCode::UNPROCESSABLE_ENTITY, body.to_owned()) {
+            Error::Github { error, status } => {
+                assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+                assert_eq!(error.message, "Validation Failed");
+            }
+            error => panic!("Unexpected error {:?}", error),
+        }
+    }
+
+    #[test]
+    fn client_error_raw_body() {
+        let body = r#"{"error": "rate limited by proxy"}"#;
+        match client_error(StatusCode::TOO_MANY_REQUESTS, body.to_owned()) {
+            Error::Api {
+                description,
+                status,
+            } => {
+                assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+                assert_eq!(description, body);
+            }
+            error => panic!("Unexpected error {:?}", error),
+        }
+    }
+
+    #[test]
+    fn link_url_next_then_last() {
+        let header = r#"<https://api.github.com/search/issues?q=repo%3Aoctocat%2FHello-World&page=2>; rel="next", <https://api.github.com/search/issues?q=repo%3Aoctocat%2FHello-World&page=34>; rel="last""#;
+        assert_eq!(
+            link_url(header, "next"),
+            Some("https://api.github.com/search/issues?q=repo%3Aoctocat%2FHello-World&page=2")
+        );
+        assert_eq!(
+            link_url(header, "last"),
+            Some("https://api.github.com/search/issues?q=repo%3Aoctocat%2FHello-World&page=34")
+        );
+    }
+
+    #[test]
+    fn link_url_all_relations() {
+        let header = r#"<https://api.github.com/repositories/1300192/issues?page=2>; rel="prev", <https://api.github.com/repositories/1300192/issues?page=4>; rel="next", <https://api.github.com/repositories/1300192/issues?page=515>; rel="last", <https://api.github.com/repositories/1300192/issues?page=1>; rel="first""#;
+        assert_eq!(
+            link_url(header, "next"),
+            Some("https://api.github.com/repositories/1300192/issues?page=4")
+        );
+        assert_eq!(
+            link_url(header, "prev"),
+            Some("https://api.github.com/repositories/1300192/issues?page=2")
+        );
+        assert_eq!(
+            link_url(header, "first"),
+            Some("https://api.github.com/repositories/1300192/issues?page=1")
+        );
+    }
+
+    #[test]
+    fn link_url_commas_in_url() {
+        let header = r#"<https://api.github.com/search/issues?q=label:"a","b"+closed:2020-01-01..2020-01-14&page=1>; rel="prev", <https://api.github.com/search/issues?q=label:"a","b"+closed:2020-01-01..2020-01-14&page=3>; rel="next""#;
+        assert_eq!(
+            link_url(header, "next"),
+            Some(
+                r#"https://api.github.com/search/issues?q=label:"a","b"+closed:2020-01-01..2020-01-14&page=3"#
+            )
+        );
+    }
+
+    #[test]
+    fn link_url_parameter_order() {
+        let header = r#"<https://example.com/page/1>; title="first page"; rel="first", <https://example.com/page/3>; type="application/json"; rel=next"#;
+        assert_eq!(link_url(header, "next"), Some("https://example.com/page/3"));
+        assert_eq!(
+            link_url(r#"<https://example.com/page/3>; rel="next last""#, "last"),
+            Some("https://example.com/page/3")
+        );
+    }
+
+    #[test]
+    fn link_url_missing() {
+        let header = r#"<https://api.github.com/repositories/1300192/issues?page=514>; rel="prev", <https://api.github.com/repositories/1300192/issues?page=1>; rel="first""#;
+        assert_eq!(link_url(header, "next"), None);
+        assert_eq!(link_url("", "next"), None);
+        assert_eq!(link_url("<https://example.com/unterminated", "next"), None);
+    }
+}
@@ -1,31 +1,53 @@
 #![deny(clippy::all)]
 
+use std::borrow::Cow;
 use std::collections::hash_map::DefaultHasher;
 use std::fmt;
 use std::hash::Hasher;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
 
 use chrono::{DateTime, FixedOffset};
+use serde::de::DeserializeOwned;
 
+pub mod api;
 mod core;
 pub mod error;
 pub mod github;
 pub mod secret;
 pub mod zenhub;
 
-pub use crate::core::{AssignedTo, Sprint};
+pub use crate::core::{
+    compute_done_points_by_closed_at, AssignedTo, ReportIssue, Sprint, SprintPoints, SprintReport,
+};
 pub use error::Error;
 use github::{
-    paginate::PaginatedSearch, Direction, Issue, IssueUpdate, Milestone, MilestoneUpdate,
-    OrganisationMember, Repository, SearchIssues, SearchQueryBuilder, State,
+    paginate::PaginatedSearch, Direction, Issue, IssueUpdate, Label, Milestone, MilestoneUpdate,
+    OrganisationMember, RateLimit, Reaction, Repository, SearchIssues, SearchQueryBuilder, State,
+    StateReason,
 };
-use zenhub::{Board, Pipeline, PipelinePosition, StartDate, Workspace};
+use zenhub::{Board, MoveOutcome, Pipeline, PipelinePosition, StartDate, Workspace};
+
+/// Maximum number of concurrent requests made by bulk operations, unless configured.
+pub const DEFAULT_MAX_CONCURRENCY: usize = 4;
+
+/// Minimum interval between bulk Zenhub requests, across all workers.
+///
+/// Zenhub allows 100 requests per minute, so this keeps all workers together under the limit.
+const ZENHUB_BULK_INTERVAL: Duration = Duration::from_millis(600);
 
 /// Decadog client, used to abstract complex tasks over several APIs.
+///
+/// The client may either borrow its configuration and API clients, or own them
+/// (see `Client::new_owned`) so that it can be stored and sent across threads.
 pub struct Client<'a> {
-    owner: &'a str,
-    repo: &'a str,
-    github: &'a github::Client,
-    zenhub: &'a zenhub::Client,
+    owner: Cow<'a, str>,
+    repo: Cow<'a, str>,
+    github: Cow<'a, github::Client>,
+    zenhub: Cow<'a, zenhub::Client>,
+    max_concurrency: usize,
 
     id: u64,
 }
@@ -36,6 +58,23 @@ impl<'a> fmt::Debug for Client<'a> {
     }
 }
 
+impl Client<'static> {
+    /// Create a new client that owns its configuration and API clients.
+    pub fn new_owned(
+        owner: String,
+        repo: String,
+        github: github::Client,
+        zenhub: zenhub::Client,
+    ) -> Result<Client<'static>, Error> {
+        Client::from_parts(
+            Cow::Owned(owner),
+            Cow::Owned(repo),
+            Cow::Owned(github),
+            Cow::Owned(zenhub),
+        )
+    }
+}
+
 impl<'a> Client<'a> {
     /// Create a new client that can make requests to the Github API using token auth.
     pub fn new(
@@ -43,6 +82,20 @@ impl<'a> Client<'a> {
         repo: &'a str,
         github: &'a github::Client,
         zenhub: &'a zenhub::Client,
+    ) -> Result<Client<'a>, Error> {
+        Client::from_parts(
+            Cow::Borrowed(owner),
+            Cow::Borrowed(repo),
+            Cow::Borrowed(github),
+            Cow::Borrowed(zenhub),
+        )
+    }
+
+    fn from_parts(
+        owner: Cow<'a, str>,
+        repo: Cow<'a, str>,
+        github: Cow<'a, github::Client>,
+        zenhub: Cow<'a, zenhub::Client>,
     ) -> Result<Client<'a>, Error> {
         let mut hasher = DefaultHasher::new();
         hasher.write(owner.as_bytes());
@@ -57,15 +110,28 @@ impl<'a> Client<'a> {
             repo,
             github,
             zenhub,
+            max_concurrency: DEFAULT_MAX_CONCURRENCY,
         })
     }
 
+    /// Set the maximum number of concurrent requests made by bulk operations.
+    ///
+    /// Values below one are treated as one.
+    pub fn set_max_concurrency(&mut self, max_concurrency: usize) {
+        self.max_concurrency = max_concurrency.max(1);
+    }
+
+    /// Maximum number of concurrent requests made by bulk operations.
+    pub fn max_concurrency(&self) -> usize {
+        self.max_concurrency
+    }
+
     pub fn owner(&self) -> &str {
-        self.owner
+        &self.owner
     }
 
     pub fn repo(&self) -> &str {
-        self.repo
+        &self.repo
     }
 
     /// Get Zenhub StartDate for a Github Milestone.
@@ -82,6 +148,11 @@ impl<'a> Client<'a> {
         self.zenhub.get_first_workspace(repository.id)
     }
 
+    /// Get all Zenhub workspaces for a repository.
+    pub fn get_workspaces(&self, repository: &Repository) -> Result<Vec<Workspace>, Error> {
+        self.zenhub.get_workspaces(repository.id)
+    }
+
     /// Get Zenhub board for a repository.
     pub fn get_board(
         &self,
@@ -100,6 +171,17 @@ impl<'a> Client<'a> {
         self.zenhub.get_issue(repository.id, issue.number)
     }
 
+    /// Get Zenhub issue metadata in the context of a workspace.
+    pub fn get_zenhub_issue_in_workspace(
+        &self,
+        repository: &Repository,
+        workspace: &Workspace,
+        issue: &Issue,
+    ) -> Result<zenhub::Issue, Error> {
+        self.zenhub
+            .get_issue_in_workspace(&workspace.id, repository.id, issue.number)
+    }
+
     /// Set Zenhub issue estimate.
     pub fn set_estimate(
         &self,
@@ -111,6 +193,66 @@ impl<'a> Client<'a> {
             .set_estimate(repository.id, issue.number, estimate)
     }
 
+    /// Set Zenhub estimates for many issues concurrently, as `(issue_number, estimate)` pairs.
+    ///
+    /// At most `max_concurrency` requests are in flight at once, and requests are throttled to
+    /// stay within Zenhub rate limits. Once `cancel` is set, no new requests are made; issues
+    /// not yet attempted are omitted from the results.
+    ///
+    /// Returns the result for each attempted issue, in the order given.
+    pub fn set_estimates(
+        &self,
+        repository: &Repository,
+        estimates: &[(u32, u32)],
+        cancel: &AtomicBool,
+    ) -> Vec<(u32, Result<(), Error>)> {
+        let next_index = AtomicUsize::new(0);
+        let results = Mutex::new(Vec::with_capacity(estimates.len()));
+        let workers = self.max_concurrency.min(estimates.len());
+        // Each worker waits in proportion to the number of workers, to share the rate limit
+        let worker_interval = ZENHUB_BULK_INTERVAL * workers as u32;
+
+        thread::scope(|scope| {
+            for _ in 0..workers {
+                scope.spawn(|| {
+                    let mut first_request = true;
+                    while !cancel.load(Ordering::SeqCst) {
+                        let index = next_index.fetch_add(1, Ordering::SeqCst);
+                        let (issue_number, estimate) = match estimates.get(index) {
+                            Some(&pair) => pair,
+                            None => break,
+                        };
+                        if !first_request {
+                            thread::sleep(worker_interval);
+                            // Cancellation may have been requested while throttled
+                            if cancel.load(Ordering::SeqCst) {
+                                break;
+                            }
+                        }
+                        first_request = false;
+
+                        let result =
+                            self.zenhub
+                                .set_estimate(repository.id, issue_number, estimate);
+                        results
+                            .lock()
+                            .expect("Bulk estimate results poisoned.")
+                            .push((index, issue_number, result));
+                    }
+                });
+            }
+        });
+
+        let mut results = results
+            .into_inner()
+            .expect("Bulk estimate results poisoned.");
+        results.sort_by_key(|(index, _, _)| *index);
+        results
+            .into_iter()
+            .map(|(_, issue_number, result)| (issue_number, result))
+            .collect()
+    }
+
     /// Get sprint for milestone.
     pub fn get_sprint(
         &self,
@@ -124,21 +266,21 @@ impl<'a> Client<'a> {
         })
     }
 
-    /// Create a new sprint.
+    /// Create a new sprint, with a milestone of the given title.
     pub fn create_sprint(
         &self,
         repository: &Repository,
-        sprint_number: &str,
+        title: &str,
         start_date: DateTime<FixedOffset>,
         due_on: DateTime<FixedOffset>,
     ) -> Result<Sprint, Error> {
         let mut milestone_spec = MilestoneUpdate::default();
-        milestone_spec.title = Some(format!("Sprint {}", sprint_number));
+        milestone_spec.title = Some(title.to_owned());
         milestone_spec.due_on = Some(due_on);
 
         let milestone = self
             .github
-            .create_milestone(self.owner, self.repo, &milestone_spec)?;
+            .create_milestone(&self.owner, &self.repo, &milestone_spec)?;
 
         let start_date = start_date.into();
         let start_date =
@@ -151,43 +293,81 @@ impl<'a> Client<'a> {
     }
 
     /// Move issue to a Zenhub pipeline.
+    ///
+    /// The issue is placed at `position` in the pipeline, or at the top if not given.
+    /// If the issue is already in `pipeline` as fetched, no request is made.
     pub fn move_issue_to_pipeline(
         &self,
         repository: &Repository,
         workspace: &Workspace,
         issue: &Issue,
         pipeline: &Pipeline,
-    ) -> Result<(), Error> {
-        let mut position = PipelinePosition::default();
-        position.pipeline_id = pipeline.id.clone();
-
-        self.zenhub
-            .move_issue(repository.id, &workspace.id, issue.number, &position)
+        position: Option<&str>,
+    ) -> Result<MoveOutcome, Error> {
+        if issue.assigned_to(pipeline) {
+            return Ok(MoveOutcome::AlreadyThere);
+        }
+
+        let mut pipeline_position = PipelinePosition::default();
+        pipeline_position.pipeline_id = pipeline.id.clone();
+        if let Some(position) = position {
+            pipeline_position.position = position.to_owned();
+        }
+
+        self.zenhub.move_issue_retrying(
+            repository.id,
+            &workspace.id,
+            issue.number,
+            &pipeline_position,
+        )?;
+        Ok(MoveOutcome::Moved)
     }
 
     /// Get a repository from the API.
     pub fn get_repository(&self) -> Result<Repository, Error> {
-        self.github.get_repository(self.owner, self.repo)
+        self.github.get_repository(&self.owner, &self.repo)
+    }
+
+    /// Get the current Github rate limit status.
+    pub fn get_rate_limit(&self) -> Result<RateLimit, Error> {
+        self.github.get_rate_limit()
     }
 
     /// Get an issue from the API.
     pub fn get_issue(&self, issue_number: u32) -> Result<Issue, Error> {
-        self.github.get_issue(self.owner, self.repo, issue_number)
+        self.github.get_issue(&self.owner, &self.repo, issue_number)
     }
 
     /// Get milestones from the API.
     pub fn get_milestones(&self) -> Result<Vec<Milestone>, Error> {
-        self.github.get_milestones(self.owner, self.repo)
+        self.github.get_milestones(&self.owner, &self.repo)
+    }
+
+    /// Get a milestone in any state by exact title, or `None` if there is no match.
+    ///
+    /// Errors if several milestones share the title.
+    pub fn get_milestone_by_title(&self, title: &str) -> Result<Option<Milestone>, Error> {
+        self.github
+            .get_milestone_by_title(&self.owner, &self.repo, title)
     }
 
     /// Assign an issue to a milestone. Passing `None` will set to no milestone.
     ///
-    /// This will overwrite an existing milestone, if present.
+    /// This will overwrite an existing milestone, if present. If the issue is already
+    /// assigned as requested, no request is made and the issue is returned unchanged.
     pub fn assign_issue_to_milestone(
         &self,
         issue: &Issue,
         milestone: Option<&Milestone>,
     ) -> Result<Issue, Error> {
+        let already_assigned = match milestone {
+            Some(milestone) => issue.assigned_to(milestone),
+            None => issue.milestone.is_none(),
+        };
+        if already_assigned {
+            return Ok(issue.clone());
+        }
+
         let mut update = IssueUpdate::default();
         update.milestone = Some(milestone.map(|milestone| milestone.number));
 
@@ -195,6 +375,49 @@ impl<'a> Client<'a> {
             .patch_issue(&self.owner, &self.repo, issue.number, &update)
     }
 
+    /// Get reactions to an issue.
+    pub fn get_issue_reactions(&self, issue: &Issue) -> Result<Vec<Reaction>, Error> {
+        self.github
+            .get_issue_reactions(&self.owner, &self.repo, issue.number)
+    }
+
+    /// Replace all labels on an issue, clearing any not given.
+    ///
+    /// Returns the resulting labels on the issue.
+    pub fn replace_issue_labels(
+        &self,
+        issue: &Issue,
+        labels: &[String],
+    ) -> Result<Vec<Label>, Error> {
+        self.github
+            .set_issue_labels(&self.owner, &self.repo, issue.number, labels)
+    }
+
+    /// Assign an issue to a milestone and set its Zenhub estimate in one step.
+    ///
+    /// Returns the updated issue.
+    pub fn finalize_issue(
+        &self,
+        repository: &Repository,
+        issue: &Issue,
+        milestone: &Milestone,
+        estimate: u32,
+    ) -> Result<Issue, Error> {
+        let issue = self.assign_issue_to_milestone(issue, Some(milestone))?;
+        self.set_estimate(repository, &issue, estimate)?;
+        Ok(issue)
+    }
+
+    /// Close an issue as not planned, such as when it is obsolete.
+    pub fn close_issue_as_not_planned(&self, issue: &Issue) -> Result<Issue, Error> {
+        let mut update = IssueUpdate::default();
+        update.state = Some(State::Closed);
+        update.state_reason = Some(StateReason::NotPlanned);
+
+        self.github
+            .patch_issue(&self.owner, &self.repo, issue.number, &update)
+    }
+
     /// Assign an organisation member to an issue.
     ///
     /// This will overwrite any existing assignees, if present.
@@ -215,21 +438,51 @@ impl<'a> Client<'a> {
         &self,
         query_builder: &mut SearchQueryBuilder,
     ) -> Result<PaginatedSearch<Issue>, Error> {
+        self.search(query_builder)
+    }
+
+    /// Get issues by the given query, in ascending order of time updated.
+    ///
+    /// Results are deserialized into any shape `T`, such as `IssueSummary`.
+    pub fn search<T>(
+        &self,
+        query_builder: &mut SearchQueryBuilder,
+    ) -> Result<PaginatedSearch<T>, Error>
+    where
+        T: DeserializeOwned,
+    {
         let query = SearchIssues {
             q: query_builder
-                .owner_repo(self.owner, self.repo)
+                .owner_repo(&self.owner, &self.repo)
                 .issue()
                 .build(),
             sort: Some("updated"),
             order: Some(Direction::Ascending),
             per_page: Some(100),
         };
-        self.github.search_issues(&query)
+        self.github.search(&query)
+    }
+
+    /// Get all repositories of the owning organisation.
+    pub fn get_org_repositories(&self) -> Result<Vec<Repository>, Error> {
+        self.github.get_org_repositories(&self.owner)
     }
 
     /// Get organisation members.
     pub fn get_members(&self) -> Result<Vec<OrganisationMember>, Error> {
-        self.github.get_members(self.owner)
+        self.github.get_members(&self.owner)
+    }
+
+    /// Get users who can be assigned to issues in the repository.
+    pub fn get_assignable_users(&self) -> Result<Vec<OrganisationMember>, Error> {
+        self.github.get_assignable_users(&self.owner, &self.repo)
+    }
+
+    /// Get the organisation member with the given login.
+    ///
+    /// Logins are matched case insensitively, as on Github. Errors if there is no such member.
+    pub fn resolve_member(&self, login: &str) -> Result<OrganisationMember, Error> {
+        self.github.resolve_member(&self.owner, login)
     }
 
     /// Update milestone title with provided title
@@ -255,6 +508,8 @@ impl<'a> Client<'a> {
 
 #[cfg(test)]
 mod tests {
+    use std::thread;
+
     use chrono::{FixedOffset, NaiveDate, TimeZone};
     use lazy_static::lazy_static;
     use mockito::mock;
@@ -326,4 +581,441 @@ mod tests {
 
         assert_eq!(issues, vec![]);
     }
+
+    #[test]
+    fn test_finalize_issue() {
+        let body = r#"{
+  "id": 1234567,
+  "number": 7,
+  "state": "closed",
+  "title": "Mock Title",
+  "assignees": [],
+  "milestone": {
+    "id": 1002604,
+    "number": 3,
+    "state": "open",
+    "title": "Sprint 3",
+    "due_on": "2012-10-09T23:39:01Z"
+  },
+  "labels": [],
+  "created_at": "2011-04-22T13:33:48Z",
+  "updated_at": "2011-04-22T13:33:48Z",
+  "closed_at": "2011-04-22T13:33:48Z",
+  "html_url": "http://foo.bar"
+}"#;
+        let github_mock = mock("PATCH", "/repos/tommilligan/decadog/issues/7")
+            .match_header("authorization", "token mock_token")
+            .match_body(r#"{"milestone":3}"#)
+            .with_status(200)
+            .with_body(body)
+            .create();
+        let zenhub_mock = mock("PUT", "/p1/repositories/1234/issues/7/estimate")
+            .match_header("x-authentication-token", "mock_token")
+            .match_body(r#"{"estimate":3}"#)
+            .with_status(200)
+            .create();
+
+        let repository = Repository {
+            id: 1234,
+            name: REPO.to_owned(),
+            full_name: format!("{}/{}", OWNER, REPO),
+        };
+        let mut milestone = Milestone::default();
+        milestone.number = 3;
+        let mut issue = Issue::default();
+        issue.number = 7;
+
+        let issue = MOCK_CLIENT
+            .finalize_issue(&repository, &issue, &milestone, 3)
+            .unwrap();
+
+        github_mock.assert();
+        zenhub_mock.assert();
+        assert_eq!(issue.number, 7);
+        assert_eq!(issue.milestone.unwrap().title, "Sprint 3");
+    }
+
+    #[test]
+    fn test_move_issue_to_pipeline_position() {
+        let mock = mock(
+            "POST",
+            "/p2/workspaces/ws_position/repositories/1234/issues/9/moves",
+        )
+        .match_header("x-authentication-token", "mock_token")
+        .match_body(r#"{"pipeline_id":"p3","position":"bottom"}"#)
+        .with_status(200)
+        .create();
+
+        let repository = Repository {
+            id: 1234,
+            name: REPO.to_owned(),
+            full_name: format!("{}/{}", OWNER, REPO),
+        };
+        let workspace = Workspace {
+            id: "ws_position".to_owned(),
+            ..Workspace::default()
+        };
+        let pipeline = Pipeline {
+            id: "p3".to_owned(),
+            name: "Done".to_owned(),
+            issues: vec![],
+        };
+        let mut issue = Issue::default();
+        issue.number = 9;
+
+        let outcome = MOCK_CLIENT
+            .move_issue_to_pipeline(&repository, &workspace, &issue, &pipeline, Some("bottom"))
+            .unwrap();
+
+        mock.assert();
+        assert_eq!(outcome, MoveOutcome::Moved);
+    }
+
+    #[test]
+    fn test_move_issue_to_pipeline_already_there() {
+        let mock = mock(
+            "POST",
+            "/p2/workspaces/ws_already/repositories/1234/issues/10/moves",
+        )
+        .expect(0)
+        .create();
+
+        let repository = Repository {
+            id: 1234,
+            name: REPO.to_owned(),
+            full_name: format!("{}/{}", OWNER, REPO),
+        };
+        let workspace = Workspace {
+            id: "ws_already".to_owned(),
+            ..Workspace::default()
+        };
+        let pipeline = Pipeline {
+            id: "p3".to_owned(),
+            name: "Done".to_owned(),
+            issues: vec![zenhub::PipelineIssue {
+                issue_number: 10,
+                estimate: None,
+                is_epic: false,
+            }],
+        };
+        let mut issue = Issue::default();
+        issue.number = 10;
+
+        let outcome = MOCK_CLIENT
+            .move_issue_to_pipeline(&repository, &workspace, &issue, &pipeline, None)
+            .unwrap();
+
+        mock.assert();
+        assert_eq!(outcome, MoveOutcome::AlreadyThere);
+    }
+
+    #[test]
+    fn test_assign_issue_to_milestone_already_assigned() {
+        let mock = mock("PATCH", "/repos/tommilligan/decadog/issues/17")
+            .expect(0)
+            .create();
+
+        let mut milestone = Milestone::default();
+        milestone.id = 1002;
+        milestone.number = 4;
+        let mut issue = Issue::default();
+        issue.number = 17;
+        issue.milestone = Some(milestone.clone());
+
+        let assigned = MOCK_CLIENT
+            .assign_issue_to_milestone(&issue, Some(&milestone))
+            .unwrap();
+        assert_eq!(assigned, issue);
+
+        issue.milestone = None;
+        let unassigned = MOCK_CLIENT.assign_issue_to_milestone(&issue, None).unwrap();
+        assert_eq!(unassigned, issue);
+
+        mock.assert();
+    }
+
+    #[test]
+    fn test_resolve_member() {
+        let body = r#"[
+  {
+    "login": "tommilligan",
+    "id": 1
+  },
+  {
+    "login": "octocat",
+    "id": 2
+  }
+]"#;
+        let mock = mock("GET", "/orgs/tommilligan/members?per_page=100")
+            .match_header("authorization", "token mock_token")
+            .with_status(200)
+            .with_body(body)
+            .expect(2)
+            .create();
+
+        let member = MOCK_CLIENT.resolve_member("OctoCat").unwrap();
+        let unknown = MOCK_CLIENT.resolve_member("nobody");
+
+        mock.assert();
+        assert_eq!(
+            member,
+            OrganisationMember {
+                login: "octocat".to_owned(),
+                id: 2
+            }
+        );
+        match unknown {
+            Err(Error::NotFound { description }) => {
+                assert_eq!(description, "'nobody' is not a member of tommilligan.")
+            }
+            other => panic!("Expected not found error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_assign_member_to_issue() {
+        let body = r#"{
+  "id": 1234568,
+  "number": 19,
+  "state": "open",
+  "title": "Mock Title",
+  "assignees": [
+    {
+      "login": "octocat",
+      "id": 2
+    }
+  ],
+  "labels": [],
+  "created_at": "2011-04-22T13:33:48Z",
+  "updated_at": "2011-04-22T13:33:48Z",
+  "html_url": "http://foo.bar"
+}"#;
+        let mock = mock("PATCH", "/repos/tommilligan/decadog/issues/19")
+            .match_header("authorization", "token mock_token")
+            .match_body(r#"{"assignees":["octocat"]}"#)
+            .with_status(200)
+            .with_body(body)
+            .create();
+
+        let mut issue = Issue::default();
+        issue.number = 19;
+        let member = OrganisationMember {
+            login: "octocat".to_owned(),
+            id: 2,
+        };
+
+        let issue = MOCK_CLIENT.assign_member_to_issue(&member, &issue).unwrap();
+
+        mock.assert();
+        assert_eq!(issue.assignees, vec![member]);
+    }
+
+    #[test]
+    fn test_close_issue_as_not_planned() {
+        let body = r#"{
+  "id": 1234567,
+  "number": 18,
+  "state": "closed",
+  "state_reason": "not_planned",
+  "title": "Obsolete",
+  "assignees": [],
+  "milestone": null,
+  "labels": [{"id": 1, "name": "Z-obsolete"}],
+  "created_at": "2011-04-22T13:33:48Z",
+  "updated_at": "2011-04-22T13:33:48Z",
+  "closed_at": "2011-04-22T13:33:48Z",
+  "html_url": "http://foo.bar"
+}"#;
+        let mock = mock("PATCH", "/repos/tommilligan/decadog/issues/18")
+            .match_header("authorization", "token mock_token")
+            .match_body(r#"{"state":"closed","state_reason":"not_planned"}"#)
+            .with_status(200)
+            .with_body(body)
+            .create();
+
+        let mut issue = Issue::default();
+        issue.number = 18;
+        let issue = MOCK_CLIENT.close_issue_as_not_planned(&issue).unwrap();
+
+        mock.assert();
+        assert_eq!(issue.state, State::Closed);
+    }
+
+    #[test]
+    fn test_get_milestone_by_title() {
+        let body = r#"[
+  {
+    "id": 1,
+    "number": 1,
+    "state": "closed",
+    "title": "Sprint 1",
+    "due_on": "2012-10-09T23:39:01Z"
+  },
+  {
+    "id": 2,
+    "number": 2,
+    "state": "open",
+    "title": "Sprint 2",
+    "due_on": "2012-10-23T23:39:01Z"
+  },
+  {
+    "id": 3,
+    "number": 3,
+    "state": "open",
+    "title": "Sprint 2",
+    "due_on": "2012-10-23T23:39:01Z"
+  }
+]"#;
+        let mock = mock(
+            "GET",
+            "/repos/tommilligan/decadog/milestones?state=all&per_page=100",
+        )
+        .match_header("authorization", "token mock_token")
+        .with_status(200)
+        .with_body(body)
+        .expect(3)
+        .create();
+
+        let milestone = MOCK_CLIENT.get_milestone_by_title("Sprint 1").unwrap();
+        assert_eq!(milestone.map(|milestone| milestone.id), Some(1));
+
+        let milestone = MOCK_CLIENT.get_milestone_by_title("Sprint 9").unwrap();
+        assert_eq!(milestone, None);
+
+        match MOCK_CLIENT.get_milestone_by_title("Sprint 2") {
+            Err(Error::Ambiguous { .. }) => (),
+            result => panic!("Expected ambiguous milestone, got {:?}", result),
+        }
+
+        mock.assert();
+    }
+
+    #[test]
+    fn test_set_estimates() {
+        let mocks = [(21, 1), (22, 5), (23, 13)]
+            .iter()
+            .map(|(issue_number, estimate)| {
+                mock(
+                    "PUT",
+                    &format!("/p1/repositories/4321/issues/{}/estimate", issue_number)[..],
+                )
+                .match_header("x-authentication-token", "mock_token")
+                .match_body(&format!(r#"{{"estimate":{}}}"#, estimate)[..])
+                .with_status(200)
+                .create()
+            })
+            .collect::<Vec<_>>();
+
+        let repository = Repository {
+            id: 4321,
+            name: REPO.to_owned(),
+            full_name: format!("{}/{}", OWNER, REPO),
+        };
+        let results = MOCK_CLIENT.set_estimates(
+            &repository,
+            &[(21, 1), (22, 5), (23, 13)],
+            &AtomicBool::new(false),
+        );
+
+        for mock in mocks {
+            mock.assert();
+        }
+        assert_eq!(
+            results
+                .iter()
+                .map(|(issue_number, result)| (*issue_number, result.is_ok()))
+                .collect::<Vec<_>>(),
+            vec![(21, true), (22, true), (23, true)]
+        );
+    }
+
+    #[test]
+    fn test_set_estimates_cancelled() {
+        let mock = mock("PUT", "/p1/repositories/4322/issues/21/estimate")
+            .expect(0)
+            .create();
+
+        let repository = Repository {
+            id: 4322,
+            name: REPO.to_owned(),
+            full_name: format!("{}/{}", OWNER, REPO),
+        };
+        let results = MOCK_CLIENT.set_estimates(&repository, &[(21, 1)], &AtomicBool::new(true));
+
+        mock.assert();
+        assert!(results.is_empty());
+    }
+
+    #[test]
+    fn test_set_estimates_cancelled_while_throttled() {
+        let first = mock("PUT", "/p1/repositories/4323/issues/21/estimate")
+            .with_status(200)
+            .create();
+        let rest = [22, 23]
+            .iter()
+            .map(|issue_number| {
+                mock(
+                    "PUT",
+                    &format!("/p1/repositories/4323/issues/{}/estimate", issue_number)[..],
+                )
+                .expect(0)
+                .create()
+            })
+            .collect::<Vec<_>>();
+
+        let mut client = Client::new(OWNER, REPO, &MOCK_GITHUB_CLIENT, &MOCK_ZENHUB_CLIENT)
+            .expect("Couldn't create mock client");
+        client.set_max_concurrency(1);
+        let repository = Repository {
+            id: 4323,
+            name: REPO.to_owned(),
+            full_name: format!("{}/{}", OWNER, REPO),
+        };
+        let cancel = AtomicBool::new(false);
+
+        // Cancel after the first request, while the worker waits to make the second
+        let results = thread::scope(|scope| {
+            scope.spawn(|| {
+                thread::sleep(ZENHUB_BULK_INTERVAL / 2);
+                cancel.store(true, Ordering::SeqCst);
+            });
+            client.set_estimates(&repository, &[(21, 1), (22, 1), (23, 1)], &cancel)
+        });
+
+        first.assert();
+        for mock in rest {
+            mock.assert();
+        }
+        assert_eq!(
+            results
+                .iter()
+                .map(|(issue_number, result)| (*issue_number, result.is_ok()))
+                .collect::<Vec<_>>(),
+            vec![(21, true)]
+        );
+    }
+
+    #[test]
+    fn owned_client_moves_across_threads() {
+        let client = Client::new_owned(
+            OWNER.to_owned(),
+            REPO.to_owned(),
+            github::Client::new(&mockito::server_url(), "mock_token").unwrap(),
+            zenhub::Client::new(&mockito::server_url(), "mock_token").unwrap(),
+        )
+        .unwrap();
+
+        let handle = thread::spawn(move || {
+            (
+                client.owner().to_owned(),
+                client.repo().to_owned(),
+                client.id,
+            )
+        });
+        let (owner, repo, id) = handle.join().unwrap();
+
+        assert_eq!(owner, OWNER);
+        assert_eq!(repo, REPO);
+        assert_eq!(id, MOCK_CLIENT.id);
+    }
 }
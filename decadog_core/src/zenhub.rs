@@ -1,13 +1,17 @@
 use std::collections::hash_map::DefaultHasher;
+use std::error::Error as StdError;
 use std::fmt;
 use std::hash::Hasher;
+use std::io;
+use std::thread;
+use std::time::Duration;
 
 use chrono::{DateTime, FixedOffset};
 use log::debug;
 use reqwest::header::HeaderMap;
 use reqwest::{
     blocking::{Client as ReqwestClient, ClientBuilder, RequestBuilder},
-    Method,
+    Method, StatusCode,
 };
 use serde::de::DeserializeOwned;
 use serde_derive::{Deserialize, Serialize};
@@ -15,6 +19,37 @@ use url::Url;
 
 use crate::error::Error;
 
+/// Maximum attempts to send a Zenhub move that fails to connect.
+const MOVE_ATTEMPTS: u32 = 3;
+
+/// Delay before retrying a Zenhub move that failed to connect.
+const MOVE_RETRY_DELAY: Duration = Duration::from_millis(250);
+
+/// Whether the request failed to connect, so was never sent.
+///
+/// The connect error is an `io::Error` somewhere in the source chain.
+fn is_connect_error(error: &reqwest::Error) -> bool {
+    let mut source = error.source();
+    while let Some(error) = source {
+        if let Some(error) = error.downcast_ref::<io::Error>() {
+            match error.kind() {
+                io::ErrorKind::ConnectionRefused
+                | io::ErrorKind::ConnectionAborted
+                | io::ErrorKind::AddrNotAvailable => return true,
+                _ => {}
+            }
+        }
+        source = error.source();
+    }
+    false
+}
+
+/// Zenhub API client.
+///
+/// Requests are not retried by default. When retrying:
+/// - reads, setting estimates and setting start dates are idempotent, so always safe to retry
+/// - moves are not, as a repeated move may reorder the pipeline; use `move_issue_retrying`
+#[derive(Clone)]
 pub struct Client {
     id: u64,
     reqwest_client: ReqwestClient,
@@ -141,21 +176,48 @@ impl Client {
         self.request(
             Method::GET,
             self.base_url
-                .join(&format!("/p2/repositories/{}/workspaces", repository_id))?,
+                .join(&format!("p2/repositories/{}/workspaces", repository_id))?,
         )
         .send_api()
     }
 
-    /// Get Zenhub board for a repository.
-    pub fn get_board(&self, repository_id: u64, workspace_id: &str) -> Result<Board, Error> {
-        self.request(
+    /// Request for a Zenhub board, overriding the client timeout if `timeout` is given.
+    fn board_request(
+        &self,
+        repository_id: u64,
+        workspace_id: &str,
+        timeout: Option<Duration>,
+    ) -> Result<RequestBuilder, Error> {
+        let request = self.request(
             Method::GET,
             self.base_url.join(&format!(
-                "/p2/workspaces/{}/repositories/{}/board",
+                "p2/workspaces/{}/repositories/{}/board",
                 workspace_id, repository_id
             ))?,
-        )
-        .send_api()
+        );
+        Ok(match timeout {
+            Some(timeout) => request.timeout(timeout),
+            None => request,
+        })
+    }
+
+    /// Get Zenhub board for a repository.
+    pub fn get_board(&self, repository_id: u64, workspace_id: &str) -> Result<Board, Error> {
+        self.board_request(repository_id, workspace_id, None)?
+            .send_api()
+    }
+
+    /// Get Zenhub board for a repository, with a timeout for this request only.
+    ///
+    /// Boards of large workspaces can be slow to fetch, so may need a longer timeout.
+    pub fn get_board_with_timeout(
+        &self,
+        repository_id: u64,
+        workspace_id: &str,
+        timeout: Duration,
+    ) -> Result<Board, Error> {
+        self.board_request(repository_id, workspace_id, Some(timeout))?
+            .send_api()
     }
 
     /// Get Zenhub StartDate for a milestone.
@@ -167,7 +229,7 @@ impl Client {
         self.request(
             Method::GET,
             self.base_url.join(&format!(
-                "/p1/repositories/{}/milestones/{}/start_date",
+                "p1/repositories/{}/milestones/{}/start_date",
                 repository_id, milestone_number
             ))?,
         )
@@ -184,7 +246,7 @@ impl Client {
         self.request(
             Method::POST,
             self.base_url.join(&format!(
-                "/p1/repositories/{}/milestones/{}/start_date",
+                "p1/repositories/{}/milestones/{}/start_date",
                 repository_id, milestone_number
             ))?,
         )
@@ -197,13 +259,40 @@ impl Client {
         self.request(
             Method::GET,
             self.base_url.join(&format!(
-                "/p1/repositories/{}/issues/{}",
+                "p1/repositories/{}/issues/{}",
                 repository_id, issue_number
             ))?,
         )
         .send_api()
     }
 
+    /// Get Zenhub issue metadata in the context of a workspace.
+    ///
+    /// Falls back to the workspace agnostic endpoint if the workspace endpoint is unavailable.
+    pub fn get_issue_in_workspace(
+        &self,
+        workspace_id: &str,
+        repository_id: u64,
+        issue_number: u32,
+    ) -> Result<Issue, Error> {
+        let workspace_issue = self
+            .request(
+                Method::GET,
+                self.base_url.join(&format!(
+                    "p2/workspaces/{}/repositories/{}/issues/{}",
+                    workspace_id, repository_id, issue_number
+                ))?,
+            )
+            .send_api();
+        match workspace_issue {
+            Err(Error::Api { status, .. }) if status == StatusCode::NOT_FOUND => {
+                debug!("Workspace issue endpoint unavailable, falling back");
+                self.get_issue(repository_id, issue_number)
+            }
+            workspace_issue => workspace_issue,
+        }
+    }
+
     /// Set Zenhub issue estimate.
     pub fn set_estimate(
         &self,
@@ -214,7 +303,7 @@ impl Client {
         self.request(
             Method::PUT,
             self.base_url.join(&format!(
-                "/p1/repositories/{}/issues/{}/estimate",
+                "p1/repositories/{}/issues/{}/estimate",
                 repository_id, issue_number
             ))?,
         )
@@ -233,13 +322,70 @@ impl Client {
         self.request(
             Method::POST,
             self.base_url.join(&format!(
-                "/p2/workspaces/{}/repositories/{}/issues/{}/moves",
+                "p2/workspaces/{}/repositories/{}/issues/{}/moves",
                 workspace_id, repository_id, issue_number
             ))?,
         )
         .json(position)
         .send_api_no_response()
     }
+
+    /// Move issue to a Zenhub pipeline, retrying only where this cannot reorder the board.
+    ///
+    /// Moves that fail to connect never reached Zenhub, so are retried. Moves that time out
+    /// may have been applied, so the board is checked instead of retrying. Moves that receive
+    /// any response are never retried.
+    pub fn move_issue_retrying(
+        &self,
+        repository_id: u64,
+        workspace_id: &str,
+        issue_number: u32,
+        position: &PipelinePosition,
+    ) -> Result<(), Error> {
+        let mut attempt = 1;
+        loop {
+            match self.move_issue(repository_id, workspace_id, issue_number, position) {
+                Err(Error::Reqwest { ref source })
+                    if is_connect_error(source) && attempt < MOVE_ATTEMPTS =>
+                {
+                    debug!("Retrying move of issue {}: {}", issue_number, source);
+                    thread::sleep(MOVE_RETRY_DELAY);
+                    attempt += 1;
+                }
+                Err(Error::Reqwest { source }) if source.is_timeout() => {
+                    let location = self
+                        .get_board(repository_id, workspace_id)?
+                        .locate(issue_number);
+                    return match location {
+                        Some(location) if location.pipeline_id == position.pipeline_id => Ok(()),
+                        _ => Err(Error::Reqwest { source }),
+                    };
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Move issue to a Zenhub pipeline, then refetch the board to locate it.
+    ///
+    /// This costs an extra request over `move_issue`.
+    pub fn move_issue_and_locate(
+        &self,
+        repository_id: u64,
+        workspace_id: &str,
+        issue_number: u32,
+        position: &PipelinePosition,
+    ) -> Result<MovedTo, Error> {
+        self.move_issue(repository_id, workspace_id, issue_number, position)?;
+        self.get_board(repository_id, workspace_id)?
+            .locate(issue_number)
+            .ok_or_else(|| Error::Unknown {
+                description: format!(
+                    "Issue {} not found on Zenhub board after move.",
+                    issue_number
+                ),
+            })
+    }
 }
 
 /// Zenhub Workspace.
@@ -258,6 +404,16 @@ pub struct Issue {
     pub is_epic: bool,
 }
 
+impl Issue {
+    /// Points for this issue. An issue with no estimate has no points.
+    pub fn points(&self) -> u32 {
+        self.estimate
+            .as_ref()
+            .map(|estimate| estimate.value)
+            .unwrap_or(0)
+    }
+}
+
 /// A Zenhub estimate.
 #[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
 pub struct Estimate {
@@ -304,6 +460,9 @@ pub struct Pipeline {
     pub issues: Vec<PipelineIssue>,
 }
 
+/// Positions an issue can be moved to within a Zenhub pipeline.
+pub const PIPELINE_POSITIONS: &[&str] = &["top", "bottom"];
+
 /// A position of an issue in a Zenhub pipeline.
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct PipelinePosition {
@@ -326,6 +485,40 @@ pub struct Board {
     pub pipelines: Vec<Pipeline>,
 }
 
+impl Board {
+    /// Find the pipeline and position of an issue on the board.
+    pub fn locate(&self, issue_number: u32) -> Option<MovedTo> {
+        self.pipelines.iter().find_map(|pipeline| {
+            pipeline
+                .issues
+                .iter()
+                .position(|issue| issue.issue_number == issue_number)
+                .map(|position| MovedTo {
+                    pipeline_id: pipeline.id.clone(),
+                    pipeline_name: pipeline.name.clone(),
+                    position,
+                })
+        })
+    }
+}
+
+/// Result of moving an issue to a Zenhub pipeline.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MoveOutcome {
+    Moved,
+    /// The issue was already in the pipeline, so was not moved.
+    AlreadyThere,
+}
+
+/// Where an issue is on a Zenhub board.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct MovedTo {
+    pub pipeline_id: String,
+    pub pipeline_name: String,
+    /// Zero-indexed position within the pipeline.
+    pub position: usize,
+}
+
 /// A Zenhub milestone StartDate.
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct StartDate {
@@ -342,6 +535,10 @@ impl From<DateTime<FixedOffset>> for StartDate {
 
 #[cfg(test)]
 pub mod tests {
+    use std::io::{self, BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::time::Instant;
+
     use lazy_static::lazy_static;
     use mockito::mock;
     use pretty_assertions::assert_eq;
@@ -367,6 +564,34 @@ pub mod tests {
         }
     }
 
+    #[test]
+    fn test_base_url_path_prefix() {
+        let client = Client::new(
+            &format!("{}/zenhub/api/", &mockito::server_url()),
+            MOCK_ZENHUB_TOKEN,
+        )
+        .unwrap();
+
+        let mock = mock("GET", "/zenhub/api/p1/repositories/1234/issues/41")
+            .match_header("x-authentication-token", "mock_token")
+            .with_status(200)
+            .with_body(r#"{"estimate": null, "is_epic": false}"#)
+            .create();
+
+        client.get_issue(1234, 41).unwrap();
+        mock.assert();
+    }
+
+    #[test]
+    fn issue_points() {
+        let estimated = Issue {
+            estimate: Some(Estimate { value: 5 }),
+            is_epic: false,
+        };
+        assert_eq!(estimated.points(), 5);
+        assert_eq!(Issue::default().points(), 0);
+    }
+
     #[test]
     fn test_get_issue() {
         let body = r#"{
@@ -393,4 +618,295 @@ pub mod tests {
             }
         );
     }
+
+    #[test]
+    fn test_get_issue_in_workspace() {
+        let body = r#"{
+    "estimate": {
+        "value": 5
+    },
+    "plus_ones": [],
+    "pipeline": {
+        "name": "In Progress",
+        "pipeline_id": "p2",
+        "workspace_id": "ws1"
+    },
+    "is_epic": true
+}"#;
+
+        let mock = mock("GET", "/p2/workspaces/ws1/repositories/1234/issues/21")
+            .match_header("x-authentication-token", "mock_token")
+            .with_status(200)
+            .with_body(body)
+            .create();
+
+        let issue = MOCK_ZENHUB_CLIENT
+            .get_issue_in_workspace("ws1", 1234, 21)
+            .unwrap();
+        mock.assert();
+
+        assert_eq!(
+            issue,
+            Issue {
+                estimate: Some(Estimate { value: 5 }),
+                is_epic: true,
+            }
+        );
+    }
+
+    #[test]
+    fn test_get_issue_in_workspace_fallback() {
+        let workspace_mock = mock("GET", "/p2/workspaces/ws1/repositories/1234/issues/22")
+            .with_status(404)
+            .with_body(r#"{"message": "Not Found"}"#)
+            .create();
+        let mock = mock("GET", "/p1/repositories/1234/issues/22")
+            .match_header("x-authentication-token", "mock_token")
+            .with_status(200)
+            .with_body(r#"{"estimate": null, "is_epic": false}"#)
+            .create();
+
+        let issue = MOCK_ZENHUB_CLIENT
+            .get_issue_in_workspace("ws1", 1234, 22)
+            .unwrap();
+        workspace_mock.assert();
+        mock.assert();
+
+        assert_eq!(
+            issue,
+            Issue {
+                estimate: None,
+                is_epic: false,
+            }
+        );
+    }
+
+    #[test]
+    fn test_move_issue_and_locate() {
+        let move_mock = mock(
+            "POST",
+            "/p2/workspaces/ws1/repositories/1234/issues/8/moves",
+        )
+        .match_header("x-authentication-token", "mock_token")
+        .match_body(r#"{"pipeline_id":"p2","position":"top"}"#)
+        .with_status(200)
+        .create();
+        let board_mock = mock("GET", "/p2/workspaces/ws1/repositories/1234/board")
+            .match_header("x-authentication-token", "mock_token")
+            .with_status(200)
+            .with_body(
+                r#"{
+  "pipelines": [
+    {
+      "id": "p1",
+      "name": "Backlog",
+      "issues": [{"issue_number": 3, "estimate": null, "is_epic": false}]
+    },
+    {
+      "id": "p2",
+      "name": "In Progress",
+      "issues": [
+        {"issue_number": 5, "estimate": {"value": 2}, "is_epic": false},
+        {"issue_number": 8, "is_epic": false}
+      ]
+    }
+  ]
+}"#,
+            )
+            .create();
+
+        let mut position = PipelinePosition::default();
+        position.pipeline_id = "p2".to_owned();
+        let moved_to = MOCK_ZENHUB_CLIENT
+            .move_issue_and_locate(1234, "ws1", 8, &position)
+            .unwrap();
+        move_mock.assert();
+        board_mock.assert();
+
+        assert_eq!(
+            moved_to,
+            MovedTo {
+                pipeline_id: "p2".to_owned(),
+                pipeline_name: "In Progress".to_owned(),
+                position: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn board_request_timeout() {
+        let request = MOCK_ZENHUB_CLIENT
+            .board_request(1234, "ws1", None)
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(request.timeout(), None);
+
+        let request = MOCK_ZENHUB_CLIENT
+            .board_request(1234, "ws1", Some(Duration::from_secs(45)))
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(request.timeout(), Some(&Duration::from_secs(45)));
+    }
+
+    #[test]
+    fn test_move_issue_retrying_not_retried_after_response() {
+        let move_mock = mock(
+            "POST",
+            "/p2/workspaces/ws_retry/repositories/1234/issues/8/moves",
+        )
+        .match_header("x-authentication-token", "mock_token")
+        .with_status(502)
+        .expect(1)
+        .create();
+        let board_mock = mock("GET", "/p2/workspaces/ws_retry/repositories/1234/board")
+            .expect(0)
+            .create();
+
+        let mut position = PipelinePosition::default();
+        position.pipeline_id = "p2".to_owned();
+        let result = MOCK_ZENHUB_CLIENT.move_issue_retrying(1234, "ws_retry", 8, &position);
+        move_mock.assert();
+        board_mock.assert();
+
+        match result {
+            Err(Error::Api { status, .. }) => assert_eq!(status, StatusCode::BAD_GATEWAY),
+            result => panic!("Unexpected result {:?}", result),
+        }
+    }
+
+    #[test]
+    fn test_move_issue_retrying_connection_error() {
+        // Nothing listens on port 1, so every attempt fails to connect
+        let client = Client::new("http://127.0.0.1:1/", MOCK_ZENHUB_TOKEN).unwrap();
+        let mut position = PipelinePosition::default();
+        position.pipeline_id = "p2".to_owned();
+
+        let started = Instant::now();
+        match client.move_issue_retrying(1234, "ws1", 8, &position) {
+            Err(Error::Reqwest { source }) => assert!(is_connect_error(&source)),
+            result => panic!("Unexpected result {:?}", result),
+        }
+        // Every attempt is made, with a delay before each retry
+        assert!(started.elapsed() >= MOVE_RETRY_DELAY * (MOVE_ATTEMPTS - 1));
+    }
+
+    /// Serve one connection for each response in turn, returning the request line of each.
+    ///
+    /// A `None` response is never sent, so the request times out.
+    fn serve_in_turn(
+        responses: Vec<Option<&'static str>>,
+    ) -> (String, thread::JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        let handle = thread::spawn(move || {
+            responses
+                .into_iter()
+                .map(|response| {
+                    let (mut stream, _) = listener.accept().unwrap();
+                    stream
+                        .set_read_timeout(Some(Duration::from_secs(5)))
+                        .unwrap();
+                    let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+                    let mut request_line = String::new();
+                    reader.read_line(&mut request_line).unwrap();
+                    let mut content_length = 0;
+                    loop {
+                        let mut header = String::new();
+                        reader.read_line(&mut header).unwrap();
+                        if header.trim().is_empty() {
+                            break;
+                        }
+                        let header = header.to_lowercase();
+                        if let Some(length) = header.strip_prefix("content-length:") {
+                            content_length = length.trim().parse().unwrap();
+                        }
+                    }
+                    reader
+                        .by_ref()
+                        .take(content_length)
+                        .read_to_end(&mut vec![])
+                        .unwrap();
+
+                    match response {
+                        Some(body) => write!(
+                            stream,
+                            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                            body.len(),
+                            body
+                        )
+                        .unwrap(),
+                        // Hold the connection until the client gives up on it
+                        None => {
+                            let _ = io::copy(&mut reader, &mut io::sink());
+                        }
+                    }
+                    request_line.trim().to_owned()
+                })
+                .collect()
+        });
+        (url, handle)
+    }
+
+    /// Zenhub client that times out quickly.
+    fn impatient_client(url: &str) -> Client {
+        Client {
+            id: 0,
+            reqwest_client: ClientBuilder::new()
+                .timeout(Duration::from_millis(250))
+                .build()
+                .unwrap(),
+            base_url: Url::parse(url).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_move_issue_retrying_timeout_applied() {
+        let (url, server) = serve_in_turn(vec![
+            None,
+            Some(
+                r#"{"pipelines": [{"id": "p2", "name": "Done", "issues": [{"issue_number": 8, "is_epic": false}]}]}"#,
+            ),
+        ]);
+        let mut position = PipelinePosition::default();
+        position.pipeline_id = "p2".to_owned();
+
+        let result = impatient_client(&url).move_issue_retrying(1234, "ws1", 8, &position);
+
+        assert!(result.is_ok(), "Unexpected result {:?}", result);
+        assert_eq!(
+            server.join().unwrap(),
+            vec![
+                "POST /p2/workspaces/ws1/repositories/1234/issues/8/moves HTTP/1.1",
+                "GET /p2/workspaces/ws1/repositories/1234/board HTTP/1.1",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_move_issue_retrying_timeout_not_applied() {
+        let (url, server) = serve_in_turn(vec![
+            None,
+            Some(
+                r#"{"pipelines": [{"id": "p1", "name": "Backlog", "issues": [{"issue_number": 8, "is_epic": false}]}]}"#,
+            ),
+        ]);
+        let mut position = PipelinePosition::default();
+        position.pipeline_id = "p2".to_owned();
+
+        match impatient_client(&url).move_issue_retrying(1234, "ws1", 8, &position) {
+            Err(Error::Reqwest { source }) => assert!(source.is_timeout()),
+            result => panic!("Unexpected result {:?}", result),
+        }
+        // The move is sent once, and never retried
+        assert_eq!(
+            server.join().unwrap(),
+            vec![
+                "POST /p2/workspaces/ws1/repositories/1234/issues/8/moves HTTP/1.1",
+                "GET /p2/workspaces/ws1/repositories/1234/board HTTP/1.1",
+            ]
+        );
+    }
 }